@@ -1,24 +1,40 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+use crate::database::WeeklyRecord;
+use crate::forecast::RiskForecast;
 
 #[derive(Serialize, Clone)]
 pub struct StudentTrend {
     pub student_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub course: Option<String>, // Set for trends built from stored weeks in a course
     pub trend: String, // "Improving", "Declining", "Stable"
     pub current_performance: String,
     pub recommendation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forecast: Option<RiskForecast>, // Projected habits and end-of-term pass probability
 }
 
 #[derive(Serialize, Clone)]
 pub struct ClassTrends {
     pub weeks: Vec<usize>,
     pub avg_study_hours: Vec<f64>,
+    pub median_study_hours: Vec<f64>,
     pub avg_attendance: Vec<f64>,
-    pub pass_rates: Vec<f64>,
+    pub median_attendance: Vec<f64>,
+    pub pass_rates: Vec<f64>, // Share of the week's records predicted to pass
+    pub student_counts: Vec<usize>,
 }
 
 pub struct TrendsAnalyzer;
 
+impl Default for TrendsAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TrendsAnalyzer {
     pub fn new() -> Self {
         TrendsAnalyzer
@@ -28,9 +44,11 @@ impl TrendsAnalyzer {
         if historical_data.is_empty() {
             return StudentTrend {
                 student_name: student_name.to_string(),
+                course: None,
                 trend: "Insufficient Data".to_string(),
                 current_performance: "Unknown".to_string(),
                 recommendation: "Need more data points".to_string(),
+                forecast: None,
             };
         }
 
@@ -72,43 +90,85 @@ impl TrendsAnalyzer {
 
         StudentTrend {
             student_name: student_name.to_string(),
+            course: None,
             trend: trend.to_string(),
             current_performance: performance.to_string(),
             recommendation: recommendation.to_string(),
+            forecast: None,
         }
     }
 
-    pub fn generate_class_trends(&self, _student_data: HashMap<String, Vec<(f64, f64)>>) -> ClassTrends {
-        // For demo purposes, generate mock trends
-        let weeks = vec![1, 2, 3, 4, 5, 6, 7, 8];
-        let avg_study_hours = vec![5.2, 5.5, 5.8, 6.1, 6.3, 6.5, 6.7, 6.8];
-        let avg_attendance = vec![75.0, 76.5, 78.0, 79.0, 80.0, 81.0, 81.5, 82.0];
-        let pass_rates = vec![0.65, 0.68, 0.72, 0.75, 0.77, 0.79, 0.81, 0.82];
-
-        ClassTrends {
-            weeks,
-            avg_study_hours,
-            avg_attendance,
-            pass_rates,
+    // Per-week mean and median habits, predicted pass rate and number of
+    // students, over the weeks anyone has a record for
+    pub fn generate_class_trends(&self, records: &[WeeklyRecord]) -> ClassTrends {
+        let mut by_week: BTreeMap<usize, Vec<&WeeklyRecord>> = BTreeMap::new();
+        for record in records {
+            by_week.entry(record.week).or_default().push(record);
+        }
+
+        let mut trends = ClassTrends {
+            weeks: Vec::new(),
+            avg_study_hours: Vec::new(),
+            median_study_hours: Vec::new(),
+            avg_attendance: Vec::new(),
+            median_attendance: Vec::new(),
+            pass_rates: Vec::new(),
+            student_counts: Vec::new(),
+        };
+
+        for (week, week_records) in by_week {
+            let hours: Vec<f64> = week_records.iter().map(|r| r.study_hours).collect();
+            let attendance: Vec<f64> = week_records.iter().map(|r| r.attendance).collect();
+            let passes = week_records.iter().filter(|r| r.predicted_pass).count();
+            let mut students: Vec<(&str, Option<&str>)> =
+                week_records.iter().map(|r| (r.student_name.as_str(), r.course.as_deref())).collect();
+            students.sort_unstable();
+            students.dedup();
+
+            trends.weeks.push(week);
+            trends.avg_study_hours.push(mean(&hours));
+            trends.median_study_hours.push(median(&hours));
+            trends.avg_attendance.push(mean(&attendance));
+            trends.median_attendance.push(median(&attendance));
+            trends.pass_rates.push(passes as f64 / week_records.len() as f64);
+            trends.student_counts.push(students.len());
         }
+
+        trends
     }
 }
 
-// Mock data generator
-pub fn generate_mock_trends_data() -> HashMap<String, Vec<(f64, f64)>> {
-    let mut data = HashMap::new();
-    
-    let students = vec![
-        ("Denis Lemayian".to_string(), vec![(4.5, 70.0), (5.0, 75.0), (5.5, 80.0), (6.0, 85.0)]),
-        ("Saitoti Smith".to_string(), vec![(6.0, 85.0), (6.5, 88.0), (7.0, 90.0), (7.5, 92.0)]),
-        ("Kukutia Johnson".to_string(), vec![(3.0, 60.0), (3.5, 65.0), (4.0, 70.0), (4.5, 72.0)]),
-        ("Kirionki Williams".to_string(), vec![(5.5, 78.0), (5.0, 75.0), (4.5, 72.0), (4.0, 68.0)]),
-        ("David Lemoita".to_string(), vec![(7.0, 88.0), (7.5, 90.0), (8.0, 92.0), (8.5, 94.0)]),
-    ];
-
-    for (name, weekly_data) in students {
-        data.insert(name, weekly_data);
+// (student name, course)
+pub type StudentCourse = (String, Option<String>);
+
+// Each student's (hours, attendance) by week in each of their courses, in
+// week order. Courses are kept apart so a student's weeks in two courses
+// are never read as one history
+pub fn student_histories(records: &[WeeklyRecord]) -> BTreeMap<StudentCourse, Vec<(f64, f64)>> {
+    let mut sorted: Vec<&WeeklyRecord> = records.iter().collect();
+    sorted.sort_by_key(|r| r.week);
+
+    let mut histories: BTreeMap<StudentCourse, Vec<(f64, f64)>> = BTreeMap::new();
+    for record in sorted {
+        histories
+            .entry((record.student_name.clone(), record.course.clone()))
+            .or_default()
+            .push((record.study_hours, record.attendance));
     }
+    histories
+}
 
-    data
-}
\ No newline at end of file
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
@@ -0,0 +1,81 @@
+// Hyperparameter search: cross-validates every configuration in the default
+// search space, writes the leaderboard and trains the winner
+//
+//     cargo run --bin search -- [data_path] [output_dir] [random_trials]
+//
+// The winning artifact can be served with MODEL_PATH, or the search can be
+// run inside the server via POST /hyperparameter-search, which registers the
+// winner in the model registry
+
+use std::env;
+use std::process;
+
+use student_classifier::config::AppConfig;
+use student_classifier::data::load_dataset;
+use student_classifier::model::train_validated_model;
+use student_classifier::predictor::Predictor;
+use student_classifier::search::{hyperparameter_search, SearchOptions, SearchSpace};
+use student_classifier::threshold::ThresholdObjective;
+
+fn main() {
+    let config = AppConfig::from_env();
+    let mut args = env::args().skip(1);
+    let data_path = args.next().unwrap_or_else(|| config.data_path.clone());
+    let output_dir = args.next().unwrap_or_else(|| "models/search".to_string());
+    let options = SearchOptions {
+        trials: args.next().and_then(|t| t.parse().ok()),
+        ..SearchOptions::default()
+    };
+
+    let records = match load_dataset(&data_path) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Failed to load data: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let leaderboard = match hyperparameter_search(&records, &config.training_config(), &SearchSpace::default(), &options) {
+        Ok(leaderboard) => leaderboard,
+        Err(e) => {
+            eprintln!("Search failed: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let leaderboard_path = format!("{}/leaderboard.json", output_dir);
+    let written = std::fs::create_dir_all(&output_dir)
+        .and_then(|_| std::fs::write(&leaderboard_path, serde_json::to_string_pretty(&leaderboard).unwrap_or_default()));
+    if let Err(e) = written {
+        eprintln!("Failed to write leaderboard to {}: {}", leaderboard_path, e);
+        process::exit(1);
+    }
+
+    println!("🏁 Scored {} configurations", leaderboard.len());
+    for entry in leaderboard.iter().take(5) {
+        let threshold = match entry.config.threshold {
+            ThresholdObjective::Fixed { threshold } => format!(", threshold {:.2}", threshold),
+            _ => String::new(),
+        };
+        println!("   #{} {}{}: score {:.3}", entry.rank, entry.config.family.display_name(), threshold, entry.score);
+    }
+
+    let best = &leaderboard[0];
+    let model = match train_validated_model(&records, &best.config) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("Training the best configuration failed: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let model_path = format!("{}/model.json", output_dir);
+    if let Err(e) = model.save(&model_path) {
+        eprintln!("Failed to write model to {}: {}", model_path, e);
+        process::exit(1);
+    }
+
+    println!("📊 Best model accuracy: {:.1}%", model.metadata().accuracy * 100.0);
+    println!("💾 Leaderboard written to {}", leaderboard_path);
+    println!("💾 Model written to {}", model_path);
+}
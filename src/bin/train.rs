@@ -0,0 +1,45 @@
+// Offline trainer: writes the same model artifact the server loads via MODEL_PATH
+//
+//     cargo run --bin train -- [data_path] [output_path]
+
+use std::env;
+use std::process;
+
+use student_classifier::config::AppConfig;
+use student_classifier::model::train_model_with_config;
+use student_classifier::predictor::Predictor;
+
+fn main() {
+    let config = AppConfig::from_env();
+    let mut args = env::args().skip(1);
+    let data_path = args.next().unwrap_or_else(|| config.data_path.clone());
+    let output_path = args
+        .next()
+        .or_else(|| config.model_path.clone())
+        .unwrap_or_else(|| "models/model.json".to_string());
+
+    let model = match train_model_with_config(&data_path, &config.training_config()) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("Training failed: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = model.save(&output_path) {
+        eprintln!("Failed to write model to {}: {}", output_path, e);
+        process::exit(1);
+    }
+
+    let info = model.metadata();
+    println!("✅ Trained {} on {} records", info.model_type, info.training_data_size);
+    println!("📊 Cross-validated accuracy: {:.1}%", info.accuracy * 100.0);
+    println!("💾 Model written to {}", output_path);
+
+    if let Some(rules) = model.rules() {
+        println!("📜 Learned rules:");
+        for rule in rules {
+            println!("   {}", rule);
+        }
+    }
+}
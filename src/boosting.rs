@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::model::{FeatureContribution, ModelInfo, PredictionExplanation, StudentFeatures, TrainingConfig, FEATURE_NAMES};
+use crate::predictor::{family_info, imputed_flags, logit, sigmoid, training_accuracy, ModelFamily, Predictor};
+use crate::tree::{format_rule, leaf_paths};
+
+// Added to the hessian sum of every node so small, nearly pure leaves don't
+// get extreme values
+const LEAF_REGULARIZATION: f64 = 1.0;
+
+// Like `TreeNode`, but every node holds a log-odds step: the Newton update
+// for the students reaching it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RegressionNode {
+    Split {
+        feature: usize,
+        threshold: f64, // Students with value <= threshold go left
+        left: usize,
+        right: usize,
+        value: f64,
+        samples: usize,
+    },
+    Leaf {
+        value: f64,
+        samples: usize,
+    },
+}
+
+impl RegressionNode {
+    pub fn value(&self) -> f64 {
+        match self {
+            RegressionNode::Split { value, .. } | RegressionNode::Leaf { value, .. } => *value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionTree {
+    nodes: Vec<RegressionNode>,
+}
+
+impl RegressionTree {
+    fn path(&self, vector: &[f64]) -> Vec<usize> {
+        let mut path = vec![0];
+        let mut index = 0;
+        while let RegressionNode::Split { feature, threshold, left, right, .. } = &self.nodes[index] {
+            index = if vector[*feature] <= *threshold { *left } else { *right };
+            path.push(index);
+        }
+        path
+    }
+
+    fn value(&self, vector: &[f64]) -> f64 {
+        let path = self.path(vector);
+        self.nodes[*path.last().unwrap_or(&0)].value()
+    }
+}
+
+// Gradient-boosted regression trees on the log-odds of passing, fitted to
+// the log-loss gradient one shallow tree at a time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientBoosting {
+    initial_log_odds: f64,
+    learning_rate: f64,
+    trees: Vec<RegressionTree>,
+    training_size: usize,
+    training_accuracy: f64,
+    #[serde(default)]
+    trained_at: String,
+}
+
+impl GradientBoosting {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        if records.is_empty() {
+            return Err("Cannot train a model without records".into());
+        }
+
+        let rows: Vec<Vec<f64>> = records.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+        let labels: Vec<f64> = records.iter().map(|r| if r.passed() { 1.0 } else { 0.0 }).collect();
+
+        // Start every student at the smoothed overall pass rate
+        let passes = labels.iter().sum::<f64>();
+        let initial_log_odds = logit((passes + 1.0) / (labels.len() as f64 + 2.0));
+        let mut scores = vec![initial_log_odds; rows.len()];
+        let mut trees = Vec::with_capacity(config.n_estimators);
+
+        for _ in 0..config.n_estimators {
+            let probabilities: Vec<f64> = scores.iter().map(|&s| sigmoid(s)).collect();
+            let gradients: Vec<f64> = labels.iter().zip(&probabilities).map(|(y, p)| y - p).collect();
+            let hessians: Vec<f64> = probabilities.iter().map(|p| p * (1.0 - p)).collect();
+
+            let mut nodes = Vec::new();
+            let indices: Vec<usize> = (0..rows.len()).collect();
+            grow(&mut nodes, &rows, &gradients, &hessians, indices, 0, config);
+            let tree = RegressionTree { nodes };
+
+            for (score, row) in scores.iter_mut().zip(&rows) {
+                *score += config.learning_rate * tree.value(row);
+            }
+            trees.push(tree);
+        }
+
+        let mut model = Self {
+            initial_log_odds,
+            learning_rate: config.learning_rate,
+            trees,
+            training_size: records.len(),
+            training_accuracy: 0.0,
+            trained_at: chrono::Utc::now().to_rfc3339(),
+        };
+        model.training_accuracy = training_accuracy(&model, records);
+
+        Ok(model)
+    }
+
+    pub fn trees(&self) -> &[RegressionTree] {
+        &self.trees
+    }
+
+    // The starting log-odds, then one if/then rule per leaf of every tree
+    // giving the log-odds adjustment it applies
+    pub fn rules(&self) -> Vec<String> {
+        let mut rules = vec![format!("START log-odds of passing at {:.3}", self.initial_log_odds)];
+
+        for (t, tree) in self.trees.iter().enumerate() {
+            let split_of = |index: usize| match &tree.nodes[index] {
+                RegressionNode::Split { feature, threshold, left, right, .. } => Some((*feature, *threshold, *left, *right)),
+                RegressionNode::Leaf { .. } => None,
+            };
+
+            for (leaf, conditions) in leaf_paths(split_of) {
+                let outcome = format!("add {:+.3} log-odds", self.learning_rate * tree.nodes[leaf].value());
+                rules.push(format!("Tree {}: {}", t + 1, format_rule(&conditions, &outcome)));
+            }
+        }
+
+        rules
+    }
+}
+
+impl Predictor for GradientBoosting {
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        let vector = features.to_vector();
+        sigmoid(self.initial_log_odds + self.learning_rate * self.trees.iter().map(|t| t.value(&vector)).sum::<f64>())
+    }
+
+    // Same decision-path attribution as `DecisionTree`, summed over the
+    // trees. The baseline is the score every student gets from the tree
+    // roots, so contributions add up exactly to the log-odds
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        let vector = features.to_vector();
+        let imputed = imputed_flags(features);
+        let mut totals = vec![0.0; vector.len()];
+        let mut baseline_log_odds = self.initial_log_odds;
+
+        for tree in &self.trees {
+            baseline_log_odds += self.learning_rate * tree.nodes[0].value();
+            let path = tree.path(&vector);
+            for pair in path.windows(2) {
+                if let RegressionNode::Split { feature, .. } = &tree.nodes[pair[0]] {
+                    totals[*feature] += self.learning_rate * (tree.nodes[pair[1]].value() - tree.nodes[pair[0]].value());
+                }
+            }
+        }
+
+        let mut contributions: Vec<FeatureContribution> = (0..vector.len())
+            .map(|j| FeatureContribution {
+                feature: FEATURE_NAMES[j].to_string(),
+                value: vector[j],
+                contribution: totals[j],
+                imputed: imputed[j],
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+        PredictionExplanation {
+            baseline_log_odds,
+            contributions,
+        }
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        family_info(ModelFamily::GradientBoosting, self.training_size, self.training_accuracy, &self.trained_at)
+    }
+}
+
+fn newton_step(gradients: &[f64], hessians: &[f64], indices: &[usize]) -> f64 {
+    let gradient: f64 = indices.iter().map(|&i| gradients[i]).sum();
+    let hessian: f64 = indices.iter().map(|&i| hessians[i]).sum();
+    gradient / (hessian + LEAF_REGULARIZATION)
+}
+
+// Grows the subtree for `indices` and returns the index of its root
+fn grow(
+    nodes: &mut Vec<RegressionNode>,
+    rows: &[Vec<f64>],
+    gradients: &[f64],
+    hessians: &[f64],
+    indices: Vec<usize>,
+    depth: usize,
+    config: &TrainingConfig,
+) -> usize {
+    let index = nodes.len();
+    let value = newton_step(gradients, hessians, &indices);
+    let samples = indices.len();
+    nodes.push(RegressionNode::Leaf { value, samples });
+
+    if depth >= config.max_depth {
+        return index;
+    }
+
+    let Some((feature, threshold)) = best_split(rows, gradients, &indices, config.min_samples_leaf.max(1)) else {
+        return index;
+    };
+
+    let (left_indices, right_indices): (Vec<usize>, Vec<usize>) =
+        indices.into_iter().partition(|&i| rows[i][feature] <= threshold);
+    let left = grow(nodes, rows, gradients, hessians, left_indices, depth + 1, config);
+    let right = grow(nodes, rows, gradients, hessians, right_indices, depth + 1, config);
+
+    nodes[index] = RegressionNode::Split { feature, threshold, left, right, value, samples };
+    index
+}
+
+// The (feature, threshold) that most reduces the squared error of the
+// gradients, if any split leaves `min_leaf` students per side
+fn best_split(rows: &[Vec<f64>], gradients: &[f64], indices: &[usize], min_leaf: usize) -> Option<(usize, f64)> {
+    let total = indices.len();
+    let total_sum: f64 = indices.iter().map(|&i| gradients[i]).sum();
+    // Squared error is minimised where sum^2 / count over both sides peaks
+    let mut best_gain = total_sum.powi(2) / total.max(1) as f64 + 1e-12;
+    let mut best = None;
+
+    for (feature, _) in FEATURE_NAMES.iter().enumerate() {
+        let mut sorted = indices.to_vec();
+        sorted.sort_by(|&a, &b| rows[a][feature].total_cmp(&rows[b][feature]));
+
+        let mut left_sum = 0.0;
+        for split in 1..total {
+            left_sum += gradients[sorted[split - 1]];
+
+            let lower = rows[sorted[split - 1]][feature];
+            let upper = rows[sorted[split]][feature];
+            if lower == upper || split < min_leaf || total - split < min_leaf {
+                continue;
+            }
+
+            let right_sum = total_sum - left_sum;
+            let gain = left_sum.powi(2) / split as f64 + right_sum.powi(2) / (total - split) as f64;
+            if gain > best_gain {
+                best_gain = gain;
+                best = Some((feature, (lower + upper) / 2.0));
+            }
+        }
+    }
+
+    best
+}
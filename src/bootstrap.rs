@@ -0,0 +1,75 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::model::{StudentFeatures, TrainingConfig};
+use crate::predictor::{Estimator, Predictor};
+
+pub const BOOTSTRAP_SEED: u64 = 42;
+// Share of the ensemble's scores the interval covers
+pub const INTERVAL_LEVEL: f64 = 0.9;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PredictionInterval {
+    pub lower: f64,
+    pub upper: f64,
+    pub level: f64,
+    pub models: usize, // Bootstrap models behind the interval
+}
+
+// Estimators of the configured family, each fitted on a resample (with
+// replacement) of the training records. How much they disagree about a
+// student shows how much the prediction depends on which students happened
+// to be in the training data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapEnsemble {
+    estimators: Vec<Estimator>,
+}
+
+impl BootstrapEnsemble {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig, seed: u64) -> Result<Self> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut estimators = Vec::with_capacity(config.bootstrap_models);
+
+        for _ in 0..config.bootstrap_models {
+            let resample: Vec<TrainingRecord> = (0..records.len())
+                .map(|_| records[rng.gen_range(0..records.len())].clone())
+                .collect();
+            estimators.push(Estimator::fit(&resample, config)?);
+        }
+
+        Ok(Self { estimators })
+    }
+
+    // Central `INTERVAL_LEVEL` range of the ensemble's P(pass), after
+    // `calibrate` maps each raw score the way the served model does
+    pub fn interval(&self, features: &StudentFeatures, calibrate: impl Fn(f64) -> f64) -> Option<PredictionInterval> {
+        if self.estimators.is_empty() {
+            return None;
+        }
+
+        let mut scores: Vec<f64> = self
+            .estimators
+            .iter()
+            .map(|e| calibrate(e.predict_proba(features)))
+            .collect();
+        scores.sort_by(|a, b| a.total_cmp(b));
+
+        let tail = (1.0 - INTERVAL_LEVEL) / 2.0;
+        Some(PredictionInterval {
+            lower: quantile(&scores, tail),
+            upper: quantile(&scores, 1.0 - tail),
+            level: INTERVAL_LEVEL,
+            models: scores.len(),
+        })
+    }
+}
+
+// Linear interpolation between the closest ranks of already sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
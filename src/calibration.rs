@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use crate::predictor::{logit, sigmoid};
+
+const PLATT_EPOCHS: usize = 1000;
+const PLATT_LEARNING_RATE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+    Platt,
+    Isotonic,
+}
+
+impl CalibrationMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "platt" | "sigmoid" => Some(CalibrationMethod::Platt),
+            "isotonic" => Some(CalibrationMethod::Isotonic),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CalibrationMethod::Platt => "Platt scaling",
+            CalibrationMethod::Isotonic => "isotonic regression",
+        }
+    }
+}
+
+// Maps a model's raw pass score onto an observed pass rate. Fitted on
+// held-out predictions, never on the rows the model was trained on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibrator {
+    // P(pass) = sigmoid(slope * logit(score) + intercept)
+    Platt { slope: f64, intercept: f64 },
+    // Piecewise-linear through the pooled blocks, flat beyond the ends
+    Isotonic { scores: Vec<f64>, pass_rates: Vec<f64> },
+}
+
+impl Calibrator {
+    // None when the held-out labels are all one class, as there is then
+    // nothing to calibrate against
+    pub fn fit(method: CalibrationMethod, scores: &[f64], labels: &[bool]) -> Option<Self> {
+        if !labels.contains(&true) || !labels.contains(&false) {
+            return None;
+        }
+
+        Some(match method {
+            CalibrationMethod::Platt => fit_platt(scores, labels),
+            CalibrationMethod::Isotonic => fit_isotonic(scores, labels),
+        })
+    }
+
+    pub fn method(&self) -> CalibrationMethod {
+        match self {
+            Calibrator::Platt { .. } => CalibrationMethod::Platt,
+            Calibrator::Isotonic { .. } => CalibrationMethod::Isotonic,
+        }
+    }
+
+    // The smallest score calibrated to at least `probability`, found by
+    // bisection since both calibrators are non-decreasing. 1 when no score
+    // reaches it
+    pub fn inverse(&self, probability: f64) -> f64 {
+        let (mut low, mut high) = (0.0, 1.0);
+        if self.apply(high) < probability {
+            return high;
+        }
+        for _ in 0..60 {
+            let mid = (low + high) / 2.0;
+            if self.apply(mid) >= probability {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        high
+    }
+
+    pub fn apply(&self, score: f64) -> f64 {
+        match self {
+            Calibrator::Platt { slope, intercept } => sigmoid(slope * logit(score) + intercept),
+            Calibrator::Isotonic { scores, pass_rates } => {
+                let upper = scores.partition_point(|&s| s < score);
+                if upper == 0 {
+                    pass_rates[0]
+                } else if upper == scores.len() {
+                    pass_rates[scores.len() - 1]
+                } else {
+                    let (x0, x1) = (scores[upper - 1], scores[upper]);
+                    let (y0, y1) = (pass_rates[upper - 1], pass_rates[upper]);
+                    y0 + (y1 - y0) * (score - x0) / (x1 - x0)
+                }
+            }
+        }
+    }
+}
+
+// Logistic fit on the logit of the score, with Platt's smoothed targets so
+// a handful of held-out students can't produce certain probabilities. The
+// slope is kept non-negative so calibration never reverses the ranking
+fn fit_platt(scores: &[f64], labels: &[bool]) -> Calibrator {
+    let passes = labels.iter().filter(|&&l| l).count() as f64;
+    let fails = labels.len() as f64 - passes;
+    let pass_target = (passes + 1.0) / (passes + 2.0);
+    let fail_target = 1.0 / (fails + 2.0);
+
+    let inputs: Vec<f64> = scores.iter().map(|&s| logit(s)).collect();
+    let targets: Vec<f64> = labels.iter().map(|&l| if l { pass_target } else { fail_target }).collect();
+    let n = inputs.len() as f64;
+
+    let mut slope = 1.0;
+    let mut intercept = 0.0;
+    for _ in 0..PLATT_EPOCHS {
+        let mut slope_grad = 0.0;
+        let mut intercept_grad = 0.0;
+        for (x, y) in inputs.iter().zip(&targets) {
+            let error = sigmoid(slope * x + intercept) - y;
+            slope_grad += error * x;
+            intercept_grad += error;
+        }
+        slope = (slope - PLATT_LEARNING_RATE * slope_grad / n).max(0.0);
+        intercept -= PLATT_LEARNING_RATE * intercept_grad / n;
+    }
+
+    Calibrator::Platt { slope, intercept }
+}
+
+// Pool-adjacent-violators: sort by score and merge neighbouring blocks until
+// the pass rate never decreases as the score rises
+fn fit_isotonic(scores: &[f64], labels: &[bool]) -> Calibrator {
+    let mut pairs: Vec<(f64, f64)> = scores
+        .iter()
+        .zip(labels)
+        .map(|(&s, &l)| (s, if l { 1.0 } else { 0.0 }))
+        .collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // (score sum, pass sum, count) per block
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+    for (score, label) in pairs {
+        blocks.push((score, label, 1.0));
+        while blocks.len() >= 2 {
+            let (s1, p1, c1) = blocks[blocks.len() - 1];
+            let (s0, p0, c0) = blocks[blocks.len() - 2];
+            if p0 / c0 < p1 / c1 {
+                break;
+            }
+            blocks.truncate(blocks.len() - 2);
+            blocks.push((s0 + s1, p0 + p1, c0 + c1));
+        }
+    }
+
+    Calibrator::Isotonic {
+        scores: blocks.iter().map(|(s, _, c)| s / c).collect(),
+        pass_rates: blocks.iter().map(|(_, p, c)| p / c).collect(),
+    }
+}
@@ -0,0 +1,74 @@
+use std::env;
+
+use crate::calibration::CalibrationMethod;
+use crate::data::DEFAULT_DATA_PATH;
+use crate::model::TrainingConfig;
+use crate::predictor::ModelFamily;
+use crate::preprocessing::{ImputationStrategy, PreprocessingConfig, ScalingMethod};
+
+// Runtime settings, read from environment variables at startup
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub data_path: String,          // STUDENT_DATA_PATH
+    pub model_path: Option<String>, // MODEL_PATH, model artifact to load or create
+    pub model_family: ModelFamily,  // MODEL_FAMILY: logistic, tree, boosting or knn
+    pub calibration: Option<CalibrationMethod>, // CALIBRATION: platt (default), isotonic or none
+    pub preprocessing: PreprocessingConfig, // SCALING (standard|minmax), IMPUTATION (defaults|mean|median), CLIP_INPUTS
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        let model_family = match env::var("MODEL_FAMILY") {
+            Ok(name) => ModelFamily::from_name(&name).unwrap_or_else(|| {
+                eprintln!("⚠️ Unknown MODEL_FAMILY '{}', using logistic regression", name);
+                ModelFamily::default()
+            }),
+            Err(_) => ModelFamily::default(),
+        };
+
+        let calibration = match env::var("CALIBRATION") {
+            Ok(name) if name.trim().eq_ignore_ascii_case("none") => None,
+            Ok(name) => Some(CalibrationMethod::from_name(&name).unwrap_or_else(|| {
+                eprintln!("⚠️ Unknown CALIBRATION '{}', using Platt scaling", name);
+                CalibrationMethod::Platt
+            })),
+            Err(_) => Some(CalibrationMethod::Platt),
+        };
+
+        let scaling = match env::var("SCALING") {
+            Ok(name) => ScalingMethod::from_name(&name).unwrap_or_else(|| {
+                eprintln!("⚠️ Unknown SCALING '{}', using standardization", name);
+                ScalingMethod::default()
+            }),
+            Err(_) => ScalingMethod::default(),
+        };
+
+        let imputation = match env::var("IMPUTATION") {
+            Ok(name) => ImputationStrategy::from_name(&name).unwrap_or_else(|| {
+                eprintln!("⚠️ Unknown IMPUTATION '{}', using the documented defaults", name);
+                ImputationStrategy::default()
+            }),
+            Err(_) => ImputationStrategy::default(),
+        };
+
+        let clip = env::var("CLIP_INPUTS").is_ok_and(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"));
+
+        Self {
+            data_path: env::var("STUDENT_DATA_PATH").unwrap_or_else(|_| DEFAULT_DATA_PATH.to_string()),
+            model_path: env::var("MODEL_PATH").ok().filter(|p| !p.is_empty()),
+            model_family,
+            calibration,
+            preprocessing: PreprocessingConfig { scaling, imputation, clip },
+        }
+    }
+
+    // Default hyperparameters for the configured model family
+    pub fn training_config(&self) -> TrainingConfig {
+        TrainingConfig {
+            family: self.model_family,
+            calibration: self.calibration,
+            preprocessing: self.preprocessing,
+            ..TrainingConfig::default()
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::Result;
+use crate::model::{StudentFeatures, TrainedModel};
+use crate::predictor::Predictor;
+
+const HOURS_STEP: f64 = 0.5;
+const ATTENDANCE_STEP: f64 = 1.0;
+// Most weekly study hours a search may suggest, which keeps the grid small
+pub const MAX_STUDY_HOURS: f64 = 80.0;
+
+// Limits on what the search may suggest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureBounds {
+    pub max_hours: f64,      // Weekly study hours
+    pub max_attendance: f64, // Percent, never above 100
+}
+
+impl Default for FeatureBounds {
+    fn default() -> Self {
+        Self {
+            max_hours: 20.0,
+            max_attendance: 100.0,
+        }
+    }
+}
+
+impl FeatureBounds {
+    // Rejects bounds, or a starting point, outside 0-`MAX_STUDY_HOURS` hours
+    // and 0-100% attendance. NaN and infinities are never in range
+    pub fn validate(&self, features: &StudentFeatures) -> Result<()> {
+        let hours = 0.0..=MAX_STUDY_HOURS;
+        let attendance = 0.0..=100.0;
+        if !hours.contains(&self.max_hours) || !hours.contains(&features.hours) {
+            return Err(format!("Study hours must be between 0 and {}", MAX_STUDY_HOURS).into());
+        }
+        if !attendance.contains(&self.max_attendance) || !attendance.contains(&features.attendance) {
+            return Err("Attendance must be between 0 and 100".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Counterfactual {
+    pub current_probability: f64,
+    pub target_probability: f64,
+    pub achievable: bool,
+    pub required_hours: f64,
+    pub required_attendance: f64,
+    pub hours_change: f64,
+    pub attendance_change: f64,
+    pub resulting_probability: f64,
+}
+
+// Searches increases to study hours and attendance (within `bounds`) for the
+// smallest change that lifts P(pass) to `target_probability`. Changes are
+// measured in training standard deviations so an extra hour and an extra
+// attendance point are weighed by how unusual they are. When no combination
+// reaches the target, the most likely one is returned with `achievable: false`
+pub fn find_counterfactual(
+    model: &TrainedModel,
+    features: &StudentFeatures,
+    target_probability: f64,
+    bounds: &FeatureBounds,
+) -> Counterfactual {
+    find_counterfactual_by(model, features, target_probability, bounds, |candidate| model.predict_proba(candidate))
+}
+
+// Same search against any probability the model can produce, e.g. the
+// chance of reaching a particular grade band
+pub fn find_counterfactual_by<F>(
+    model: &TrainedModel,
+    features: &StudentFeatures,
+    target_probability: f64,
+    bounds: &FeatureBounds,
+    probability_of: F,
+) -> Counterfactual
+where
+    F: Fn(&StudentFeatures) -> f64,
+{
+    let current_probability = probability_of(features);
+    let max_hours = bounds.max_hours.min(MAX_STUDY_HOURS);
+    let max_attendance = bounds.max_attendance.min(100.0);
+    let hours_scale = model.feature_std("study_hours").unwrap_or(1.0);
+    let attendance_scale = model.feature_std("attendance").unwrap_or(1.0);
+
+    let mut best_reaching: Option<(f64, StudentFeatures, f64)> = None;
+    let mut most_likely = (features.clone(), current_probability);
+
+    let hour_steps = steps(features.hours, max_hours, HOURS_STEP);
+    let attendance_steps = steps(features.attendance, max_attendance, ATTENDANCE_STEP);
+
+    for i in 0..=hour_steps {
+        for j in 0..=attendance_steps {
+            let mut candidate = features.clone();
+            candidate.hours = (features.hours + i as f64 * HOURS_STEP).min(max_hours.max(features.hours));
+            candidate.attendance = (features.attendance + j as f64 * ATTENDANCE_STEP).min(max_attendance.max(features.attendance));
+
+            let probability = probability_of(&candidate);
+            let cost = (candidate.hours - features.hours) / hours_scale
+                + (candidate.attendance - features.attendance) / attendance_scale;
+
+            if probability >= target_probability
+                && best_reaching.as_ref().is_none_or(|(best_cost, _, _)| cost < *best_cost)
+            {
+                best_reaching = Some((cost, candidate.clone(), probability));
+            }
+            if probability > most_likely.1 {
+                most_likely = (candidate, probability);
+            }
+        }
+    }
+
+    let achievable = best_reaching.is_some();
+    let (chosen, resulting_probability) = match best_reaching {
+        Some((_, candidate, probability)) => (candidate, probability),
+        None => most_likely,
+    };
+
+    Counterfactual {
+        current_probability,
+        target_probability,
+        achievable,
+        required_hours: chosen.hours,
+        required_attendance: chosen.attendance,
+        hours_change: chosen.hours - features.hours,
+        attendance_change: chosen.attendance - features.attendance,
+        resulting_probability,
+    }
+}
+
+// Number of `step`-sized increases from `current` up to `max`, rounding up so
+// `max` itself is always tried
+fn steps(current: f64, max: f64, step: f64) -> usize {
+    if max <= current {
+        0
+    } else {
+        ((max - current) / step).ceil() as usize
+    }
+}
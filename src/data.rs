@@ -1,17 +1,158 @@
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::io::Read;
 use csv::Reader;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Deserialize;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+pub const DEFAULT_DATA_PATH: &str = "data/students.csv";
+
+// Columns the model reads. Any other column is kept in `groups`, where
+// fairness audits look up the attributes they are asked to compare
+pub const DATASET_COLUMNS: [&str; 6] = ["hours", "attendance", "previous_grades", "sleep_hours", "pass", "grade"];
+
+// (train, test) records
+pub type Split = (Vec<TrainingRecord>, Vec<TrainingRecord>);
+
+// One labelled row of the training CSV
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrainingRecord {
+    pub hours: f64,
+    pub attendance: f64,
+    pub previous_grades: f64,
+    pub sleep_hours: f64,
+    pub pass: f64, // 1.0 = passed, 0.0 = failed
+    #[serde(default)]
+    pub grade: Option<String>, // One of model::GRADE_BANDS, when known
+    #[serde(skip)]
+    pub groups: BTreeMap<String, String>, // Attribute column -> value; blank values are left out
+}
+
+impl TrainingRecord {
+    pub fn passed(&self) -> bool {
+        self.pass >= 0.5
+    }
+}
+
 //function to validate CSV data exists
 pub fn validate_data(path: &str) -> Result<usize> {
     let mut rdr = Reader::from_path(path)?;
     let mut count = 0;
-    
+
     for result in rdr.records() {
         let _record = result?;
         count += 1;
     }
-    
+
     Ok(count)
-}
\ No newline at end of file
+}
+
+// Load every labelled record from a students CSV
+pub fn load_dataset(path: &str) -> Result<Vec<TrainingRecord>> {
+    let file = std::fs::File::open(path)?;
+    read_dataset(file).map_err(|e| format!("{}: {}", path, e).into())
+}
+
+// Parse labelled records from CSV text, e.g. uploaded in a request body
+pub fn parse_dataset(csv_text: &str) -> Result<Vec<TrainingRecord>> {
+    read_dataset(csv_text.as_bytes())
+}
+
+fn read_dataset<R: Read>(reader: R) -> Result<Vec<TrainingRecord>> {
+    let mut rdr = Reader::from_reader(reader);
+    let headers = rdr.headers()?.clone();
+    let group_columns: Vec<(usize, &str)> = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| !DATASET_COLUMNS.contains(name))
+        .collect();
+    let mut records = Vec::new();
+
+    for result in rdr.records() {
+        let row = result?;
+        let mut record: TrainingRecord = row.deserialize(Some(&headers))?;
+        for &(index, name) in &group_columns {
+            if let Some(value) = row.get(index).map(str::trim).filter(|v| !v.is_empty()) {
+                record.groups.insert(name.to_string(), value.to_string());
+            }
+        }
+        records.push(record);
+    }
+
+    if records.is_empty() {
+        return Err("No training records found".into());
+    }
+
+    Ok(records)
+}
+
+// Shuffle with a fixed seed and hold out `test_fraction` of the records.
+// Both sides always keep at least one record when there are two or more
+pub fn train_test_split(records: &[TrainingRecord], test_fraction: f64, seed: u64) -> Split {
+    let mut shuffled = records.to_vec();
+    shuffled.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    let test_size = if shuffled.len() < 2 {
+        0
+    } else {
+        ((shuffled.len() as f64 * test_fraction).round() as usize).clamp(1, shuffled.len() - 1)
+    };
+
+    let test = shuffled.split_off(shuffled.len() - test_size);
+    (shuffled, test)
+}
+
+// Like `train_test_split`, but splits passes and fails separately so both
+// sides keep the dataset's pass rate
+pub fn stratified_train_test_split(records: &[TrainingRecord], test_fraction: f64, seed: u64) -> Split {
+    let (passed, failed): (Vec<TrainingRecord>, Vec<TrainingRecord>) =
+        records.iter().cloned().partition(|r| r.passed());
+
+    let (mut train, mut test) = train_test_split(&passed, test_fraction, seed);
+    let (failed_train, failed_test) = train_test_split(&failed, test_fraction, seed);
+    train.extend(failed_train);
+    test.extend(failed_test);
+
+    (train, test)
+}
+
+// Stratified k-fold: each class is shuffled with `seed` and dealt round-robin
+// into `k` folds. Returns one (train, test) pair per fold; `k` is capped at
+// the number of records
+pub fn stratified_k_fold(records: &[TrainingRecord], k: usize, seed: u64) -> Vec<Split> {
+    let k = k.clamp(2, records.len().max(2));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut fold_of = vec![0; records.len()];
+
+    let mut next_fold = 0;
+    for class in [true, false] {
+        let mut indices: Vec<usize> = (0..records.len()).filter(|&i| records[i].passed() == class).collect();
+        indices.shuffle(&mut rng);
+        for i in indices {
+            fold_of[i] = next_fold % k;
+            next_fold += 1;
+        }
+    }
+
+    let mut splits = Vec::new();
+    for fold in 0..k {
+        let mut train = Vec::new();
+        let mut test = Vec::new();
+        for (record, &record_fold) in records.iter().zip(&fold_of) {
+            if record_fold == fold {
+                test.push(record.clone());
+            } else {
+                train.push(record.clone());
+            }
+        }
+        if !train.is_empty() && !test.is_empty() {
+            splits.push((train, test));
+        }
+    }
+
+    splits
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqlitePool, Pool, Sqlite, Row}; // ADDED: Row import
+use sqlx::{sqlite::SqlitePool, Pool, Sqlite, Row, Transaction}; // ADDED: Row import
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,20 +8,98 @@ pub struct StudentRecord {
     pub name: String,
     pub study_hours: f64,
     pub attendance: f64,
+    pub previous_grades: Option<f64>,
+    pub sleep_hours: Option<f64>,
     pub predicted_pass: bool,
     pub confidence: f64,
+    pub predicted_grade: Option<String>,
+    pub model_version: Option<String>, // Version that made the prediction
     pub created_at: DateTime<Utc>,
 }
 
+// A challenger's silent prediction for a stored (champion) prediction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowPrediction {
+    pub id: i64,
+    pub prediction_id: i64,
+    pub model_version: String,
+    pub predicted_pass: bool,
+    pub confidence: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+// A champion prediction next to a challenger's, with the student's outcome
+// when it is known
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowComparison {
+    pub champion_version: Option<String>,
+    pub champion_pass: bool,
+    pub champion_confidence: f64,
+    pub challenger_pass: bool,
+    pub challenger_confidence: f64,
+    pub actual_pass: Option<bool>,
+}
+
+// Name stored with /predict requests, which carry no student identity
+pub const ANONYMOUS_STUDENT: &str = "Anonymous Student";
+
+// What actually happened to a student, recorded after the fact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudentOutcome {
+    pub name: String,
+    pub passed: bool,
+    pub grade: Option<String>, // One of grades::GRADE_BANDS, when known
+    pub recorded_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelVersion {
     pub id: i64,
     pub version: String,
     pub accuracy: f64,
     pub features_used: String,
+    pub training_data_size: i64,
+    pub is_active: bool,
+    #[serde(skip)]
+    pub artifact: String, // Serialized TrainedModel
+    pub created_at: DateTime<Utc>,
+}
+
+// A stored evaluation (or other analysis) of one model version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelReport {
+    pub id: i64,
+    pub model_version: String,
+    pub kind: String, // "evaluation", ...
+    pub report: serde_json::Value,
     pub created_at: DateTime<Utc>,
 }
 
+// One week of a student's habits in a course, scored by the model active
+// when it was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyRecord {
+    pub id: i64,
+    pub student_name: String,
+    pub course: Option<String>,
+    pub cohort: Option<String>,
+    pub week: usize,
+    pub study_hours: f64,
+    pub attendance: f64,
+    pub predicted_pass: bool,
+    pub confidence: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+// Restricts class trends to one course and/or cohort
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrendFilter {
+    #[serde(default)]
+    pub course: Option<String>,
+    #[serde(default)]
+    pub cohort: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeeklyTrend {
     pub week: usize,
@@ -31,6 +109,13 @@ pub struct WeeklyTrend {
     pub total_students: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeBreakdown {
+    pub grade: String,
+    pub count: i64,
+    pub pass_rate: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassStatistics {
     pub total_students: i64,
@@ -56,6 +141,23 @@ impl Database {
                 name TEXT NOT NULL,
                 study_hours REAL NOT NULL,
                 attendance REAL NOT NULL,
+                previous_grades REAL,
+                sleep_hours REAL,
+                predicted_pass BOOLEAN NOT NULL,
+                confidence REAL NOT NULL,
+                predicted_grade TEXT,
+                model_version TEXT,
+                created_at DATETIME NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS shadow_predictions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                prediction_id INTEGER NOT NULL REFERENCES predictions(id),
+                model_version TEXT NOT NULL,
                 predicted_pass BOOLEAN NOT NULL,
                 confidence REAL NOT NULL,
                 created_at DATETIME NOT NULL
@@ -67,58 +169,315 @@ impl Database {
             r#"
             CREATE TABLE IF NOT EXISTS model_versions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                version TEXT NOT NULL,
+                version TEXT NOT NULL UNIQUE,
                 accuracy REAL NOT NULL,
                 features_used TEXT NOT NULL,
+                training_data_size INTEGER NOT NULL,
+                is_active BOOLEAN NOT NULL DEFAULT 0,
+                artifact TEXT NOT NULL,
+                created_at DATETIME NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        // Every activation is logged so a bad model can be rolled back
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS model_activations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                version TEXT NOT NULL,
+                activated_at DATETIME NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS model_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                model_version TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                report TEXT NOT NULL,
                 created_at DATETIME NOT NULL
             )
             "#
         ).execute(&pool).await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS weekly_records (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                student_name TEXT NOT NULL,
+                course TEXT,
+                cohort TEXT,
+                week INTEGER NOT NULL,
+                study_hours REAL NOT NULL,
+                attendance REAL NOT NULL,
+                predicted_pass BOOLEAN NOT NULL,
+                confidence REAL NOT NULL,
+                recorded_at DATETIME NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        // One outcome per student; recording it again replaces the label
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS outcomes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                passed BOOLEAN NOT NULL,
+                grade TEXT,
+                recorded_at DATETIME NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
         Ok(Database { pool })
     }
 
-    pub async fn save_prediction(&self, record: &StudentRecord) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    pub async fn save_prediction(&self, record: &StudentRecord) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
             r#"
-            INSERT INTO predictions (name, study_hours, attendance, predicted_pass, confidence, created_at)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO predictions (name, study_hours, attendance, previous_grades, sleep_hours, predicted_pass, confidence, predicted_grade, model_version, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(&record.name)
         .bind(record.study_hours)
         .bind(record.attendance)
+        .bind(record.previous_grades)
+        .bind(record.sleep_hours)
         .bind(record.predicted_pass)
         .bind(record.confidence)
+        .bind(&record.predicted_grade)
+        .bind(&record.model_version)
         .bind(record.created_at)
         .execute(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(result.last_insert_rowid())
     }
 
-    pub async fn save_model_version(&self, version: &ModelVersion) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    pub async fn save_shadow_prediction(&self, shadow: &ShadowPrediction) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
             r#"
-            INSERT INTO model_versions (version, accuracy, features_used, created_at)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO shadow_predictions (prediction_id, model_version, predicted_pass, confidence, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(shadow.prediction_id)
+        .bind(&shadow.model_version)
+        .bind(shadow.predicted_pass)
+        .bind(shadow.confidence)
+        .bind(shadow.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    // Every prediction `challenger_version` shadowed, oldest first
+    pub async fn get_shadow_comparisons(&self, challenger_version: &str) -> Result<Vec<ShadowComparison>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.model_version, p.predicted_pass AS champion_pass, p.confidence AS champion_confidence,
+                   s.predicted_pass AS challenger_pass, s.confidence AS challenger_confidence, o.passed AS actual_pass
+            FROM shadow_predictions s
+            JOIN predictions p ON p.id = s.prediction_id
+            LEFT JOIN outcomes o ON o.name = p.name
+            WHERE s.model_version = ?
+            ORDER BY s.id
+            "#
+        )
+        .bind(challenger_version)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ShadowComparison {
+                champion_version: row.get("model_version"),
+                champion_pass: row.get("champion_pass"),
+                champion_confidence: row.get("champion_confidence"),
+                challenger_pass: row.get("challenger_pass"),
+                challenger_confidence: row.get("challenger_confidence"),
+                actual_pass: row.get("actual_pass"),
+            })
+            .collect())
+    }
+
+    // Stores `version` under the next number, 1.0.0, 1.0.1, ..., ignoring its
+    // `version` field. The number comes from the highest id in the same
+    // statement as the insert, so concurrent registrations never share one.
+    // Returns the new id and version
+    pub async fn save_model_version(&self, version: &ModelVersion) -> Result<(i64, String), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO model_versions (version, accuracy, features_used, training_data_size, is_active, artifact, created_at)
+            SELECT '1.0.' || COALESCE(MAX(id), 0), ?, ?, ?, ?, ?, ?
+            FROM model_versions
             "#
         )
-        .bind(&version.version)
         .bind(version.accuracy)
         .bind(&version.features_used)
+        .bind(version.training_data_size)
+        .bind(version.is_active)
+        .bind(&version.artifact)
         .bind(version.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        let row = sqlx::query("SELECT version FROM model_versions WHERE id = ?")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok((id, row.get("version")))
+    }
+
+    pub async fn list_model_versions(&self) -> Result<Vec<ModelVersion>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, version, accuracy, features_used, training_data_size, is_active, artifact, created_at
+            FROM model_versions
+            ORDER BY id DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(model_version_from_row).collect())
+    }
+
+    pub async fn get_model_version(&self, version: &str) -> Result<Option<ModelVersion>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, version, accuracy, features_used, training_data_size, is_active, artifact, created_at
+            FROM model_versions
+            WHERE version = ?
+            "#
+        )
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(model_version_from_row))
+    }
+
+    pub async fn get_active_model_version(&self) -> Result<Option<ModelVersion>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, version, accuracy, features_used, training_data_size, is_active, artifact, created_at
+            FROM model_versions
+            WHERE is_active
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(model_version_from_row))
+    }
+
+    pub async fn activate_model_version(&self, version: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE model_versions SET is_active = (version = ?)")
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO model_activations (version, activated_at) VALUES (?, ?)")
+            .bind(version)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    // The version that was active before the current one, if any. Rolling
+    // back pops the newer activations, so repeated calls walk further back
+    pub async fn previous_active_version(&self) -> Result<Option<String>, sqlx::Error> {
+        let rows = sqlx::query("SELECT version FROM model_activations ORDER BY id DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut versions = rows.iter().map(|row| row.get::<String, _>("version"));
+        let current = versions.next();
+
+        Ok(versions.find(|v| Some(v) != current.as_ref()))
+    }
+
+    // Reactivate `version` and drop every activation logged after its most
+    // recent one, instead of logging a new activation
+    pub async fn rollback_to_version(&self, version: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM model_activations WHERE id > (SELECT MAX(id) FROM model_activations WHERE version = ?)")
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE model_versions SET is_active = (version = ?)")
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    pub async fn save_model_report(&self, model_version: &str, kind: &str, report: &serde_json::Value) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO model_reports (model_version, kind, report, created_at)
+            VALUES (?, ?, ?, ?)
+            "#
+        )
+        .bind(model_version)
+        .bind(kind)
+        .bind(report.to_string())
+        .bind(Utc::now())
         .execute(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_model_reports(&self, model_version: &str) -> Result<Vec<ModelReport>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, model_version, kind, report, created_at
+            FROM model_reports
+            WHERE model_version = ?
+            ORDER BY id DESC
+            "#
+        )
+        .bind(model_version)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ModelReport {
+                id: row.get("id"),
+                model_version: row.get("model_version"),
+                kind: row.get("kind"),
+                report: serde_json::from_str(row.get::<&str, _>("report")).unwrap_or(serde_json::Value::Null),
+                created_at: row.get("created_at"),
+            })
+            .collect())
     }
 
     pub async fn get_all_predictions(&self) -> Result<Vec<StudentRecord>, sqlx::Error> {
         // Use query instead of query_as! to avoid macro issues
         let rows = sqlx::query(
             r#"
-            SELECT id, name, study_hours, attendance, predicted_pass, confidence, created_at
+            SELECT id, name, study_hours, attendance, previous_grades, sleep_hours, predicted_pass, confidence, predicted_grade, model_version, created_at
             FROM predictions
             ORDER BY created_at DESC
             "#
@@ -126,22 +485,90 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let mut predictions = Vec::new();
-        for row in rows {
-            predictions.push(StudentRecord {
-                id: row.get("id"),
-                name: row.get("name"),
-                study_hours: row.get("study_hours"),
-                attendance: row.get("attendance"),
-                predicted_pass: row.get("predicted_pass"),
-                confidence: row.get("confidence"),
-                created_at: row.get("created_at"),
-            });
+        Ok(rows.iter().map(prediction_from_row).collect())
+    }
+
+    // The `limit` most recent predictions, newest first
+    pub async fn get_recent_predictions(&self, limit: i64) -> Result<Vec<StudentRecord>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, study_hours, attendance, previous_grades, sleep_hours, predicted_pass, confidence, predicted_grade, model_version, created_at
+            FROM predictions
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(prediction_from_row).collect())
+    }
+
+    // Attach a student's outcome to their stored predictions. Returns how
+    // many predictions it labels; nothing is stored when there are none.
+    // Anonymous predictions belong to no one student and are never labelled
+    pub async fn save_outcome(&self, outcome: &StudentOutcome) -> Result<i64, sqlx::Error> {
+        if outcome.name == ANONYMOUS_STUDENT {
+            return Ok(0);
+        }
+
+        let row = sqlx::query("SELECT COUNT(*) as prediction_count FROM predictions WHERE name = ?")
+            .bind(&outcome.name)
+            .fetch_one(&self.pool)
+            .await?;
+        let predictions = row.get::<i64, _>("prediction_count");
+        if predictions == 0 {
+            return Ok(0);
         }
 
+        sqlx::query(
+            r#"
+            INSERT INTO outcomes (name, passed, grade, recorded_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET passed = excluded.passed, grade = excluded.grade, recorded_at = excluded.recorded_at
+            "#
+        )
+        .bind(&outcome.name)
+        .bind(outcome.passed)
+        .bind(&outcome.grade)
+        .bind(outcome.recorded_at)
+        .execute(&self.pool)
+        .await?;
+
         Ok(predictions)
     }
 
+    // Each labelled student's most recent prediction with their outcome
+    pub async fn get_labelled_predictions(&self) -> Result<Vec<(StudentRecord, StudentOutcome)>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.name, p.study_hours, p.attendance, p.previous_grades, p.sleep_hours, p.predicted_pass,
+                   p.confidence, p.predicted_grade, p.model_version, p.created_at, o.passed, o.grade, o.recorded_at
+            FROM predictions p
+            JOIN outcomes o ON o.name = p.name
+            WHERE p.id = (SELECT MAX(id) FROM predictions WHERE name = p.name)
+            ORDER BY o.id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let prediction = prediction_from_row(row);
+                let outcome = StudentOutcome {
+                    name: prediction.name.clone(),
+                    passed: row.get("passed"),
+                    grade: row.get("grade"),
+                    recorded_at: row.get("recorded_at"),
+                };
+                (prediction, outcome)
+            })
+            .collect())
+    }
+
     pub async fn get_class_statistics(&self) -> Result<ClassStatistics, sqlx::Error> {
         // Use query instead of query! to avoid macro issues
         let row = sqlx::query(
@@ -165,40 +592,176 @@ impl Database {
         })
     }
 
-    pub async fn get_weekly_trends(&self) -> Result<Vec<WeeklyTrend>, sqlx::Error> {
-        // For demo purposes, return mock data
-        // In a real implementation, you'd group by week and calculate averages
-        let mock_trends = vec![
-            WeeklyTrend {
-                week: 1,
-                avg_study_hours: 5.2,
-                avg_attendance: 75.0,
-                pass_rate: 0.65,
-                total_students: 45,
-            },
-            WeeklyTrend {
-                week: 2,
-                avg_study_hours: 5.8,
-                avg_attendance: 78.0,
-                pass_rate: 0.72,
-                total_students: 52,
-            },
-            WeeklyTrend {
-                week: 3,
-                avg_study_hours: 6.1,
-                avg_attendance: 80.0,
-                pass_rate: 0.78,
-                total_students: 48,
-            },
-            WeeklyTrend {
-                week: 4,
-                avg_study_hours: 6.5,
-                avg_attendance: 82.0,
-                pass_rate: 0.81,
-                total_students: 55,
-            },
-        ];
-
-        Ok(mock_trends)
-    }
-}
\ No newline at end of file
+    // Stored predictions grouped by predicted grade band. Grades are derived
+    // from the same P(pass) as the Pass/Fail call, so "Fail" always has a
+    // pass rate of 0 and every other band 1
+    pub async fn get_grade_breakdown(&self) -> Result<Vec<GradeBreakdown>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                predicted_grade,
+                COUNT(*) as count,
+                AVG(CASE WHEN predicted_pass THEN 1.0 ELSE 0.0 END) as pass_rate
+            FROM predictions
+            WHERE predicted_grade IS NOT NULL
+            GROUP BY predicted_grade
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| GradeBreakdown {
+                grade: row.get("predicted_grade"),
+                count: row.get("count"),
+                pass_rate: row.get::<Option<f64>, _>("pass_rate").unwrap_or(0.0),
+            })
+            .collect())
+    }
+
+    // Replaces the student's record for that week of the course, if any
+    pub async fn save_weekly_record(&self, record: &WeeklyRecord) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let id = Self::replace_weekly_record(&mut tx, record).await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    // Saves every week or none of them
+    pub async fn save_weekly_records(&self, records: &[WeeklyRecord]) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            Self::replace_weekly_record(&mut tx, record).await?;
+        }
+        tx.commit().await?;
+        Ok(records.len())
+    }
+
+    async fn replace_weekly_record(tx: &mut Transaction<'_, Sqlite>, record: &WeeklyRecord) -> Result<i64, sqlx::Error> {
+        sqlx::query("DELETE FROM weekly_records WHERE student_name = ? AND week = ? AND course IS ?")
+            .bind(&record.student_name)
+            .bind(record.week as i64)
+            .bind(&record.course)
+            .execute(&mut **tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO weekly_records (student_name, course, cohort, week, study_hours, attendance, predicted_pass, confidence, recorded_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&record.student_name)
+        .bind(&record.course)
+        .bind(&record.cohort)
+        .bind(record.week as i64)
+        .bind(record.study_hours)
+        .bind(record.attendance)
+        .bind(record.predicted_pass)
+        .bind(record.confidence)
+        .bind(record.recorded_at)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    // Weekly records matching `filter`, by student then week
+    pub async fn get_weekly_records(&self, filter: &TrendFilter) -> Result<Vec<WeeklyRecord>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, student_name, course, cohort, week, study_hours, attendance, predicted_pass, confidence, recorded_at
+            FROM weekly_records
+            WHERE (? IS NULL OR course = ?) AND (? IS NULL OR cohort = ?)
+            ORDER BY student_name, week
+            "#
+        )
+        .bind(&filter.course)
+        .bind(&filter.course)
+        .bind(&filter.cohort)
+        .bind(&filter.cohort)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WeeklyRecord {
+                id: row.get("id"),
+                student_name: row.get("student_name"),
+                course: row.get("course"),
+                cohort: row.get("cohort"),
+                week: row.get::<i64, _>("week") as usize,
+                study_hours: row.get("study_hours"),
+                attendance: row.get("attendance"),
+                predicted_pass: row.get("predicted_pass"),
+                confidence: row.get("confidence"),
+                recorded_at: row.get("recorded_at"),
+            })
+            .collect())
+    }
+
+    // Per-week averages of the weekly records matching `filter`
+    pub async fn get_weekly_trends(&self, filter: &TrendFilter) -> Result<Vec<WeeklyTrend>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                week,
+                AVG(study_hours) as avg_study_hours,
+                AVG(attendance) as avg_attendance,
+                AVG(CASE WHEN predicted_pass THEN 1.0 ELSE 0.0 END) as pass_rate,
+                COUNT(DISTINCT json_array(student_name, course)) as total_students
+            FROM weekly_records
+            WHERE (? IS NULL OR course = ?) AND (? IS NULL OR cohort = ?)
+            GROUP BY week
+            ORDER BY week
+            "#
+        )
+        .bind(&filter.course)
+        .bind(&filter.course)
+        .bind(&filter.cohort)
+        .bind(&filter.cohort)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WeeklyTrend {
+                week: row.get::<i64, _>("week") as usize,
+                avg_study_hours: row.get::<Option<f64>, _>("avg_study_hours").unwrap_or(0.0),
+                avg_attendance: row.get::<Option<f64>, _>("avg_attendance").unwrap_or(0.0),
+                pass_rate: row.get::<Option<f64>, _>("pass_rate").unwrap_or(0.0),
+                total_students: row.get("total_students"),
+            })
+            .collect())
+    }
+}
+
+fn model_version_from_row(row: &sqlx::sqlite::SqliteRow) -> ModelVersion {
+    ModelVersion {
+        id: row.get("id"),
+        version: row.get("version"),
+        accuracy: row.get("accuracy"),
+        features_used: row.get("features_used"),
+        training_data_size: row.get("training_data_size"),
+        is_active: row.get("is_active"),
+        artifact: row.get("artifact"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn prediction_from_row(row: &sqlx::sqlite::SqliteRow) -> StudentRecord {
+    StudentRecord {
+        id: row.get("id"),
+        name: row.get("name"),
+        study_hours: row.get("study_hours"),
+        attendance: row.get("attendance"),
+        previous_grades: row.get("previous_grades"),
+        sleep_hours: row.get("sleep_hours"),
+        predicted_pass: row.get("predicted_pass"),
+        confidence: row.get("confidence"),
+        predicted_grade: row.get("predicted_grade"),
+        model_version: row.get("model_version"),
+        created_at: row.get("created_at"),
+    }
+}
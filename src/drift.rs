@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data::TrainingRecord;
+use crate::database::StudentRecord;
+use crate::model::{StudentFeatures, FEATURE_NAMES};
+
+// Population stability index above which a shift counts as significant
+pub const PSI_DRIFT_THRESHOLD: f64 = 0.25;
+// Two-sample KS coefficient for a 5% significance level
+const KS_COEFFICIENT: f64 = 1.358;
+const PSI_BINS: usize = 10;
+// Keeps empty bins from making the PSI infinite
+const PSI_EPSILON: f64 = 1e-4;
+// Fewer incoming values than this are compared but never flagged; a handful
+// of predictions says nothing about the population
+pub const MIN_DRIFT_SAMPLE: usize = 20;
+pub const MAX_DRIFT_WINDOW_SIZE: usize = 1000;
+pub const MAX_DRIFT_WINDOWS: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureDrift {
+    pub feature: String,
+    pub sample_size: usize, // Incoming values; imputed features are not counted
+    pub psi: f64,
+    pub ks_statistic: f64,
+    pub ks_critical_value: f64,
+    // PSI above `PSI_DRIFT_THRESHOLD` or KS above its critical value, on at
+    // least `MIN_DRIFT_SAMPLE` values
+    pub drifted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriftWindow {
+    pub window: usize, // 0 is the most recent
+    pub sample_size: usize,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub features: Vec<FeatureDrift>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriftReport {
+    pub reference_size: usize,
+    pub window_size: usize,
+    pub windows: Vec<DriftWindow>,
+    pub drifted_features: Vec<String>, // In the most recent window
+    pub retraining_recommended: bool,
+}
+
+// Compares the features of stored predictions (newest first) with the
+// training data, in consecutive windows of `window_size` predictions going
+// back from the newest. Older predictions that do not fill a window are left
+// out. Retraining is recommended when any feature has drifted in the most
+// recent window
+pub fn monitor_drift(reference: &[TrainingRecord], predictions: &[StudentRecord], window_size: usize) -> DriftReport {
+    let window_size = window_size.max(1);
+    let reference_vectors: Vec<Vec<f64>> = reference.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+
+    let windows: Vec<DriftWindow> = predictions
+        .chunks_exact(window_size)
+        .enumerate()
+        .map(|(window, chunk)| DriftWindow {
+            window,
+            sample_size: chunk.len(),
+            from: chunk.last().map_or_else(Utc::now, |p| p.created_at),
+            to: chunk.first().map_or_else(Utc::now, |p| p.created_at),
+            features: (0..FEATURE_NAMES.len())
+                .filter_map(|j| {
+                    let incoming: Vec<f64> = chunk.iter().filter_map(|p| supplied_value(p, j)).collect();
+                    let baseline: Vec<f64> = reference_vectors.iter().map(|v| v[j]).collect();
+                    feature_drift(FEATURE_NAMES[j], &baseline, &incoming)
+                })
+                .collect(),
+        })
+        .collect();
+
+    let drifted_features: Vec<String> = windows
+        .first()
+        .map(|w| w.features.iter().filter(|f| f.drifted).map(|f| f.feature.clone()).collect())
+        .unwrap_or_default();
+
+    DriftReport {
+        reference_size: reference.len(),
+        window_size,
+        retraining_recommended: !drifted_features.is_empty(),
+        drifted_features,
+        windows,
+    }
+}
+
+// The value of feature `j` (in `FEATURE_NAMES` order) the student supplied
+fn supplied_value(prediction: &StudentRecord, j: usize) -> Option<f64> {
+    match j {
+        0 => Some(prediction.study_hours),
+        1 => Some(prediction.attendance),
+        2 => prediction.previous_grades,
+        3 => prediction.sleep_hours,
+        _ => None,
+    }
+}
+
+// None when either side has no values to compare
+pub fn feature_drift(feature: &str, reference: &[f64], incoming: &[f64]) -> Option<FeatureDrift> {
+    if reference.is_empty() || incoming.is_empty() {
+        return None;
+    }
+
+    let (n, m) = (reference.len() as f64, incoming.len() as f64);
+    let psi = population_stability_index(reference, incoming, PSI_BINS);
+    let ks_statistic = ks_statistic(reference, incoming);
+    let ks_critical_value = KS_COEFFICIENT * ((n + m) / (n * m)).sqrt();
+
+    Some(FeatureDrift {
+        feature: feature.to_string(),
+        sample_size: incoming.len(),
+        psi,
+        ks_statistic,
+        ks_critical_value,
+        drifted: incoming.len() >= MIN_DRIFT_SAMPLE && (psi > PSI_DRIFT_THRESHOLD || ks_statistic > ks_critical_value),
+    })
+}
+
+// PSI over bins cut at the reference quantiles, so each bin holds about the
+// same share of training students
+pub fn population_stability_index(reference: &[f64], incoming: &[f64], bins: usize) -> f64 {
+    let mut sorted = reference.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mut edges: Vec<f64> = (1..bins)
+        .map(|b| sorted[(b * sorted.len() / bins).min(sorted.len() - 1)])
+        .collect();
+    edges.dedup();
+
+    let shares = |values: &[f64]| {
+        let mut counts = vec![0usize; edges.len() + 1];
+        for &v in values {
+            counts[edges.partition_point(|&edge| edge <= v)] += 1;
+        }
+        counts
+            .into_iter()
+            .map(|c| (c as f64 / values.len() as f64).max(PSI_EPSILON))
+            .collect::<Vec<f64>>()
+    };
+
+    shares(reference)
+        .iter()
+        .zip(shares(incoming))
+        .map(|(expected, actual)| (actual - expected) * (actual / expected).ln())
+        .sum()
+}
+
+// Largest gap between the two empirical distribution functions
+pub fn ks_statistic(a: &[f64], b: &[f64]) -> f64 {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_by(|x, y| x.total_cmp(y));
+    b.sort_by(|x, y| x.total_cmp(y));
+
+    let mut values: Vec<f64> = a.iter().chain(&b).cloned().collect();
+    values.sort_by(|x, y| x.total_cmp(y));
+    values.dedup();
+
+    values
+        .iter()
+        .map(|&v| {
+            let cdf_a = a.partition_point(|&x| x <= v) as f64 / a.len() as f64;
+            let cdf_b = b.partition_point(|&x| x <= v) as f64 / b.len() as f64;
+            (cdf_a - cdf_b).abs()
+        })
+        .fold(0.0, f64::max)
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{stratified_k_fold, Result, TrainingRecord};
+use crate::model::{StudentFeatures, TrainedModel, TrainingConfig};
+use crate::predictor::Predictor;
+
+// Probabilities are clamped this far from 0 and 1 so log loss stays finite
+const LOG_LOSS_EPSILON: f64 = 1e-15;
+pub const DEFAULT_CALIBRATION_BINS: usize = 5;
+pub const DEFAULT_CV_FOLDS: usize = 5;
+pub const DEFAULT_CV_SEED: u64 = 42;
+
+// "Positive" means the student passed
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ConfusionMatrix {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    pub observed_pass_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvaluationReport {
+    pub sample_size: usize,
+    pub accuracy: f64,
+    pub confusion_matrix: ConfusionMatrix,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1_score: f64,
+    pub roc_auc: f64,
+    pub log_loss: f64,
+    pub calibration: Vec<CalibrationBin>,
+    pub expected_calibration_error: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FoldMetrics {
+    pub fold: usize,
+    pub train_size: usize,
+    pub test_size: usize,
+    pub accuracy: f64,
+    pub f1_score: f64,
+    pub roc_auc: f64,
+    pub log_loss: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetricSummary {
+    pub mean: f64,
+    pub variance: f64,
+    pub std_dev: f64,
+}
+
+impl MetricSummary {
+    pub fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self { mean: 0.0, variance: 0.0, std_dev: 0.0 };
+        }
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+        Self { mean, variance, std_dev: variance.sqrt() }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrossValidationReport {
+    pub k: usize,
+    pub seed: u64,
+    pub folds: Vec<FoldMetrics>,
+    pub accuracy: MetricSummary,
+    pub f1_score: MetricSummary,
+    pub roc_auc: MetricSummary,
+    pub log_loss: MetricSummary,
+}
+
+// Fit `config` on each stratified fold's training side and score its test side
+pub fn cross_validate(records: &[TrainingRecord], config: &TrainingConfig, k: usize, seed: u64) -> Result<CrossValidationReport> {
+    cross_validate_with_predictions(records, config, k, seed).map(|(report, _)| report)
+}
+
+// Like `cross_validate`, but also returns the out-of-fold P(pass) and true
+// outcome for every record, i.e. predictions from a model that never saw it
+pub fn cross_validate_with_predictions(
+    records: &[TrainingRecord],
+    config: &TrainingConfig,
+    k: usize,
+    seed: u64,
+) -> Result<(CrossValidationReport, Vec<(f64, bool)>)> {
+    let splits = stratified_k_fold(records, k, seed);
+    if splits.is_empty() {
+        return Err("Not enough records for cross-validation".into());
+    }
+
+    let mut folds = Vec::new();
+    let mut out_of_fold = Vec::with_capacity(records.len());
+    for (fold, (train, test)) in splits.iter().enumerate() {
+        let model = TrainedModel::fit(train, config)?;
+        let report = evaluate_model(&model, test);
+        out_of_fold.extend(test.iter().map(|r| (model.predict_proba(&StudentFeatures::from(r)), r.passed())));
+        folds.push(FoldMetrics {
+            fold: fold + 1,
+            train_size: train.len(),
+            test_size: test.len(),
+            accuracy: report.accuracy,
+            f1_score: report.f1_score,
+            roc_auc: report.roc_auc,
+            log_loss: report.log_loss,
+        });
+    }
+
+    let summarize = |metric: fn(&FoldMetrics) -> f64| {
+        MetricSummary::from_values(&folds.iter().map(metric).collect::<Vec<f64>>())
+    };
+
+    let report = CrossValidationReport {
+        k: folds.len(),
+        seed,
+        accuracy: summarize(|f| f.accuracy),
+        f1_score: summarize(|f| f.f1_score),
+        roc_auc: summarize(|f| f.roc_auc),
+        log_loss: summarize(|f| f.log_loss),
+        folds,
+    };
+
+    Ok((report, out_of_fold))
+}
+
+pub fn evaluate_model(model: &dyn Predictor, records: &[TrainingRecord]) -> EvaluationReport {
+    let probabilities: Vec<f64> = records
+        .iter()
+        .map(|r| model.predict_proba(&StudentFeatures::from(r)))
+        .collect();
+    let labels: Vec<bool> = records.iter().map(|r| r.passed()).collect();
+
+    evaluate_probabilities(&probabilities, &labels, model.decision_threshold())
+}
+
+// Score predicted pass probabilities against the true outcomes
+pub fn evaluate_probabilities(probabilities: &[f64], labels: &[bool], threshold: f64) -> EvaluationReport {
+    let confusion_matrix = confusion_matrix(probabilities, labels, threshold);
+    let precision = ratio(
+        confusion_matrix.true_positives,
+        confusion_matrix.true_positives + confusion_matrix.false_positives,
+    );
+    let recall = ratio(
+        confusion_matrix.true_positives,
+        confusion_matrix.true_positives + confusion_matrix.false_negatives,
+    );
+    let f1_score = if precision + recall > 0.0 {
+        2.0 * precision * recall / (precision + recall)
+    } else {
+        0.0
+    };
+
+    EvaluationReport {
+        sample_size: labels.len(),
+        accuracy: ratio(
+            confusion_matrix.true_positives + confusion_matrix.true_negatives,
+            labels.len(),
+        ),
+        confusion_matrix,
+        precision,
+        recall,
+        f1_score,
+        roc_auc: roc_auc(probabilities, labels),
+        log_loss: log_loss(probabilities, labels),
+        calibration: calibration_table(probabilities, labels, DEFAULT_CALIBRATION_BINS),
+        expected_calibration_error: expected_calibration_error(probabilities, labels, DEFAULT_CALIBRATION_BINS),
+    }
+}
+
+pub fn confusion_matrix(probabilities: &[f64], labels: &[bool], threshold: f64) -> ConfusionMatrix {
+    let mut matrix = ConfusionMatrix::default();
+
+    for (&p, &passed) in probabilities.iter().zip(labels) {
+        match (p >= threshold, passed) {
+            (true, true) => matrix.true_positives += 1,
+            (true, false) => matrix.false_positives += 1,
+            (false, false) => matrix.true_negatives += 1,
+            (false, true) => matrix.false_negatives += 1,
+        }
+    }
+
+    matrix
+}
+
+// Probability that a random passing student is scored above a random failing
+// one (ties count half). Undefined with only one class, reported as 0.5
+pub fn roc_auc(probabilities: &[f64], labels: &[bool]) -> f64 {
+    let positives: Vec<f64> = probabilities.iter().zip(labels).filter(|(_, &l)| l).map(|(&p, _)| p).collect();
+    let negatives: Vec<f64> = probabilities.iter().zip(labels).filter(|(_, &l)| !l).map(|(&p, _)| p).collect();
+
+    if positives.is_empty() || negatives.is_empty() {
+        return 0.5;
+    }
+
+    let mut wins = 0.0;
+    for p in &positives {
+        for n in &negatives {
+            if p > n {
+                wins += 1.0;
+            } else if p == n {
+                wins += 0.5;
+            }
+        }
+    }
+
+    wins / (positives.len() * negatives.len()) as f64
+}
+
+pub fn log_loss(probabilities: &[f64], labels: &[bool]) -> f64 {
+    if labels.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = probabilities
+        .iter()
+        .zip(labels)
+        .map(|(&p, &passed)| {
+            let p = p.clamp(LOG_LOSS_EPSILON, 1.0 - LOG_LOSS_EPSILON);
+            if passed { -p.ln() } else { -(1.0 - p).ln() }
+        })
+        .sum();
+
+    total / labels.len() as f64
+}
+
+// Reliability table: equal-width probability bins with the observed pass rate
+// in each. Empty bins are left out
+pub fn calibration_table(probabilities: &[f64], labels: &[bool], bins: usize) -> Vec<CalibrationBin> {
+    let bins = bins.max(1);
+    let mut counts = vec![0usize; bins];
+    let mut predicted = vec![0.0; bins];
+    let mut observed = vec![0.0; bins];
+
+    for (&p, &passed) in probabilities.iter().zip(labels) {
+        let index = ((p * bins as f64) as usize).min(bins - 1);
+        counts[index] += 1;
+        predicted[index] += p;
+        if passed {
+            observed[index] += 1.0;
+        }
+    }
+
+    (0..bins)
+        .filter(|&i| counts[i] > 0)
+        .map(|i| CalibrationBin {
+            lower: i as f64 / bins as f64,
+            upper: (i + 1) as f64 / bins as f64,
+            count: counts[i],
+            mean_predicted: predicted[i] / counts[i] as f64,
+            observed_pass_rate: observed[i] / counts[i] as f64,
+        })
+        .collect()
+}
+
+// Gap between predicted and observed pass rates, averaged over the
+// reliability bins weighted by how many students fall in each
+pub fn expected_calibration_error(probabilities: &[f64], labels: &[bool], bins: usize) -> f64 {
+    if labels.is_empty() {
+        return 0.0;
+    }
+
+    calibration_table(probabilities, labels, bins)
+        .iter()
+        .map(|bin| bin.count as f64 * (bin.mean_predicted - bin.observed_pass_rate).abs())
+        .sum::<f64>()
+        / labels.len() as f64
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::evaluation::{calibration_table, confusion_matrix, expected_calibration_error, CalibrationBin, DEFAULT_CALIBRATION_BINS};
+use crate::model::StudentFeatures;
+use crate::predictor::Predictor;
+
+// How the model treats the students sharing one value of an attribute
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupMetrics {
+    pub group: String,
+    pub count: usize,
+    pub actual_pass_rate: f64,
+    pub predicted_pass_rate: f64,
+    pub true_positive_rate: f64, // Passing students predicted to pass
+    pub false_negative_rate: f64, // Passing students predicted to fail
+    pub expected_calibration_error: f64,
+    pub calibration: Vec<CalibrationBin>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttributeReport {
+    pub attribute: String,
+    pub groups: Vec<GroupMetrics>,
+    // Largest gap in true positive rate between any two groups; 0 when the
+    // model gives passing students the same chance whatever their group
+    pub equal_opportunity_difference: f64,
+    // Largest gap in predicted pass rate between any two groups
+    pub demographic_parity_difference: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FairnessReport {
+    pub sample_size: usize,
+    pub decision_threshold: f64,
+    pub attributes: Vec<AttributeReport>, // One per audited attribute, alphabetical
+}
+
+// Scores every record once and breaks the results down by each of
+// `attributes`, columns the caller declares as demographic or cohort data.
+// Other extra columns, such as student ids or names, are never grouped on.
+// Students without a value for an attribute are left out of its groups
+pub fn fairness_audit(model: &dyn Predictor, records: &[TrainingRecord], attributes: &[String]) -> Result<FairnessReport> {
+    if attributes.is_empty() {
+        return Err("Name at least one attribute column to audit".into());
+    }
+
+    // attribute -> group -> record indices
+    let mut groups_by_attribute: BTreeMap<&str, BTreeMap<&str, Vec<usize>>> = BTreeMap::new();
+    for attribute in attributes {
+        let groups = groups_by_attribute.entry(attribute.as_str()).or_default();
+        for (i, record) in records.iter().enumerate() {
+            if let Some(group) = record.groups.get(attribute) {
+                groups.entry(group.as_str()).or_default().push(i);
+            }
+        }
+        if groups.is_empty() {
+            return Err(format!("The data has no values for attribute '{}'", attribute).into());
+        }
+    }
+
+    let threshold = model.decision_threshold();
+    let probabilities: Vec<f64> = records
+        .iter()
+        .map(|r| model.predict_proba(&StudentFeatures::from(r)))
+        .collect();
+
+    let attributes = groups_by_attribute
+        .into_iter()
+        .map(|(attribute, groups)| {
+            let groups: Vec<GroupMetrics> = groups
+                .into_iter()
+                .map(|(group, indices)| {
+                    let group_probabilities: Vec<f64> = indices.iter().map(|&i| probabilities[i]).collect();
+                    let labels: Vec<bool> = indices.iter().map(|&i| records[i].passed()).collect();
+                    group_metrics(group, &group_probabilities, &labels, threshold)
+                })
+                .collect();
+
+            AttributeReport {
+                attribute: attribute.to_string(),
+                equal_opportunity_difference: spread(groups.iter().filter(|g| g.actual_pass_rate > 0.0).map(|g| g.true_positive_rate)),
+                demographic_parity_difference: spread(groups.iter().map(|g| g.predicted_pass_rate)),
+                groups,
+            }
+        })
+        .collect();
+
+    Ok(FairnessReport {
+        sample_size: records.len(),
+        decision_threshold: threshold,
+        attributes,
+    })
+}
+
+fn group_metrics(group: &str, probabilities: &[f64], labels: &[bool], threshold: f64) -> GroupMetrics {
+    let matrix = confusion_matrix(probabilities, labels, threshold);
+    let count = labels.len();
+    let passes = matrix.true_positives + matrix.false_negatives;
+    let true_positive_rate = if passes == 0 { 0.0 } else { matrix.true_positives as f64 / passes as f64 };
+
+    GroupMetrics {
+        group: group.to_string(),
+        count,
+        actual_pass_rate: passes as f64 / count as f64,
+        predicted_pass_rate: (matrix.true_positives + matrix.false_positives) as f64 / count as f64,
+        true_positive_rate,
+        false_negative_rate: if passes == 0 { 0.0 } else { 1.0 - true_positive_rate },
+        expected_calibration_error: expected_calibration_error(probabilities, labels, DEFAULT_CALIBRATION_BINS),
+        calibration: calibration_table(probabilities, labels, DEFAULT_CALIBRATION_BINS),
+    }
+}
+
+// Max minus min, or 0 for fewer than two values
+fn spread(values: impl Iterator<Item = f64>) -> f64 {
+    let values: Vec<f64> = values.collect();
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let max = values.iter().cloned().fold(f64::MIN, f64::max);
+    let min = values.iter().cloned().fold(f64::MAX, f64::min);
+    max - min
+}
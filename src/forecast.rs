@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bootstrap::INTERVAL_LEVEL;
+use crate::counterfactual::MAX_STUDY_HOURS;
+use crate::data::Result;
+use crate::model::StudentFeatures;
+use crate::predictor::Predictor;
+
+pub const DEFAULT_FORECAST_WEEKS: usize = 4;
+// No week, observed or projected, lies past the end of a year-long term
+pub const MAX_TERM_WEEKS: usize = 52;
+// Two-sided standard normal quantile for `INTERVAL_LEVEL`
+const BAND_Z: f64 = 1.645;
+
+// One week of a student's study habits
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WeeklyObservation {
+    pub week: usize,
+    pub study_hours: f64,
+    pub attendance: f64,
+}
+
+impl WeeklyObservation {
+    // Rejects weeks outside 1-`MAX_TERM_WEEKS`, study hours outside
+    // 0-`MAX_STUDY_HOURS` and attendance outside 0-100%. NaN is never in range
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_TERM_WEEKS).contains(&self.week) {
+            return Err(format!("Week must be between 1 and {}", MAX_TERM_WEEKS).into());
+        }
+        if !(0.0..=MAX_STUDY_HOURS).contains(&self.study_hours) {
+            return Err(format!("Study hours must be between 0 and {}", MAX_STUDY_HOURS).into());
+        }
+        if !(0.0..=100.0).contains(&self.attendance) {
+            return Err("Attendance must be between 0 and 100".into());
+        }
+        Ok(())
+    }
+}
+
+// Least-squares line through (week, value)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LinearTrend {
+    pub slope: f64, // Change per week
+    pub intercept: f64,
+    pub residual_std: Option<f64>, // None with only two weeks of history, which any line fits exactly
+    weeks: usize,
+    mean_week: f64,
+    week_spread: f64, // Sum of squared deviations of the weeks
+}
+
+impl LinearTrend {
+    // None with fewer than two distinct weeks
+    pub fn fit(points: &[(f64, f64)]) -> Option<Self> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_week = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_value = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let week_spread: f64 = points.iter().map(|p| (p.0 - mean_week).powi(2)).sum();
+        if week_spread == 0.0 {
+            return None;
+        }
+
+        let slope = points.iter().map(|p| (p.0 - mean_week) * (p.1 - mean_value)).sum::<f64>() / week_spread;
+        let intercept = mean_value - slope * mean_week;
+        let residual_std = (points.len() > 2).then(|| {
+            let squared: f64 = points.iter().map(|p| (p.1 - intercept - slope * p.0).powi(2)).sum();
+            (squared / (n - 2.0)).sqrt()
+        });
+
+        Some(Self {
+            slope,
+            intercept,
+            residual_std,
+            weeks: points.len(),
+            mean_week,
+            week_spread,
+        })
+    }
+
+    // Projected value with an `INTERVAL_LEVEL` prediction band, which widens
+    // the further `week` is from the observed weeks. The band is unavailable
+    // when there is no residual spread to estimate it from
+    pub fn project(&self, week: f64) -> Band {
+        let value = self.intercept + self.slope * week;
+        let spread = 1.0 + 1.0 / self.weeks as f64 + (week - self.mean_week).powi(2) / self.week_spread;
+        let margin = self.residual_std.map(|std| BAND_Z * std * spread.sqrt());
+
+        Band {
+            value,
+            lower: margin.map(|m| value - m),
+            upper: margin.map(|m| value + m),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Band {
+    pub value: f64,
+    pub lower: Option<f64>, // None when the band is unavailable
+    pub upper: Option<f64>,
+}
+
+impl Band {
+    fn clamp(self, low: f64, high: f64) -> Self {
+        Self {
+            value: self.value.clamp(low, high),
+            lower: self.lower.map(|v| v.clamp(low, high)),
+            upper: self.upper.map(|v| v.clamp(low, high)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectedWeek {
+    pub week: usize,
+    pub study_hours: Band,
+    pub attendance: Band, // Kept within 0-100%
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskForecast {
+    pub history_weeks: usize,
+    pub end_of_term_week: usize,
+    pub hours_per_week_change: f64,
+    pub attendance_per_week_change: f64,
+    pub projections: Vec<ProjectedWeek>,
+    // The classifier's P(pass) on the projected end-of-term habits
+    pub end_of_term_pass_probability: f64,
+    // Lowest and highest P(pass) over the corners of the end-of-term bands;
+    // None when the bands are unavailable
+    pub pass_probability_lower: Option<f64>,
+    pub pass_probability_upper: Option<f64>,
+    pub band_level: f64,
+}
+
+// Fits a linear trend to weekly hours and attendance, projects them
+// `weeks_ahead` weeks past the last observed week and scores the end-of-term
+// projection with `model`. `previous_grades` and `sleep_hours` are held at
+// the values given. None with fewer than two distinct weeks of history, or
+// when the projection would run past `MAX_TERM_WEEKS`
+pub fn forecast_risk(
+    model: &dyn Predictor,
+    history: &[WeeklyObservation],
+    weeks_ahead: usize,
+    previous_grades: Option<f64>,
+    sleep_hours: Option<f64>,
+) -> Option<RiskForecast> {
+    let hours: Vec<(f64, f64)> = history.iter().map(|o| (o.week as f64, o.study_hours)).collect();
+    let attendance: Vec<(f64, f64)> = history.iter().map(|o| (o.week as f64, o.attendance)).collect();
+    let hours_trend = LinearTrend::fit(&hours)?;
+    let attendance_trend = LinearTrend::fit(&attendance)?;
+
+    let last_week = history.iter().map(|o| o.week).max()?;
+    let end_week = last_week.checked_add(weeks_ahead.max(1)).filter(|&week| week <= MAX_TERM_WEEKS)?;
+    let projections: Vec<ProjectedWeek> = (last_week + 1..=end_week)
+        .map(|week| ProjectedWeek {
+            week,
+            study_hours: hours_trend.project(week as f64).clamp(0.0, f64::MAX),
+            attendance: attendance_trend.project(week as f64).clamp(0.0, 100.0),
+        })
+        .collect();
+
+    let end = projections.last()?;
+    let score = |hours: f64, attendance: f64| {
+        model.predict_proba(&StudentFeatures {
+            hours,
+            attendance,
+            previous_grades,
+            sleep_hours,
+        })
+    };
+    let corners: Option<Vec<f64>> = [end.study_hours.lower, end.study_hours.upper]
+        .iter()
+        .flat_map(|&h| [end.attendance.lower, end.attendance.upper].map(|a| Some(score(h?, a?))))
+        .collect();
+    let pass_probability = score(end.study_hours.value, end.attendance.value);
+
+    Some(RiskForecast {
+        history_weeks: history.len(),
+        end_of_term_week: end.week,
+        hours_per_week_change: hours_trend.slope,
+        attendance_per_week_change: attendance_trend.slope,
+        pass_probability_lower: corners.as_ref().map(|c| c.iter().cloned().fold(pass_probability, f64::min)),
+        pass_probability_upper: corners.as_ref().map(|c| c.iter().cloned().fold(pass_probability, f64::max)),
+        end_of_term_pass_probability: pass_probability,
+        projections,
+        band_level: INTERVAL_LEVEL,
+    })
+}
+
+// Rejects observed weeks, or a projection, running past `MAX_TERM_WEEKS`
+pub fn validate_forecast(history: &[WeeklyObservation], weeks_ahead: usize) -> Result<()> {
+    let last_week = history.iter().map(|o| o.week).max().unwrap_or(0);
+    match last_week.checked_add(weeks_ahead.max(1)) {
+        Some(end_week) if end_week <= MAX_TERM_WEEKS => Ok(()),
+        _ => Err(format!("The last week plus weeks_ahead must be at most {}, the length of a term", MAX_TERM_WEEKS).into()),
+    }
+}
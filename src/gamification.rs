@@ -100,6 +100,7 @@ enum AchievementCondition {
     PerfectAttendance(i32),
     PointsEarned(i32),
     SessionsCompleted(i32),
+    #[allow(dead_code)] // Needs per-subject tracking before it can be awarded
     SubjectMastery(String, i32),
 }
 
@@ -109,11 +110,18 @@ enum BadgeCondition {
     WeeklyChampion,
     MarathonStudier(f64),
     PerfectWeek,
+    #[allow(dead_code)] // Needs per-subject tracking before it can be awarded
     SubjectExpert(String),
     EarlyBird,
     NightOwl,
 }
 
+impl Default for GamificationEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GamificationEngine {
     pub fn new() -> Self {
         let mut achievements = HashMap::new();
@@ -233,7 +241,7 @@ impl GamificationEngine {
         let total_study_hours: f64 = profile.study_sessions.iter().map(|s| s.duration_hours).sum();
         let total_sessions = profile.study_sessions.len() as i32;
 
-        for (_, template) in &self.achievements {
+        for template in self.achievements.values() {
             if !profile.achievements.iter().any(|a| a.name == template.name) {
                 let progress = match &template.condition {
                     AchievementCondition::TotalStudyHours(target) => {
@@ -275,7 +283,7 @@ impl GamificationEngine {
         let mut new_badges = Vec::new();
         let total_sessions = profile.study_sessions.len();
 
-        for (_, template) in &self.badges {
+        for template in self.badges.values() {
             if !profile.badges.iter().any(|b| b.name == template.name) {
                 let earned = match &template.condition {
                     BadgeCondition::FirstStudySession => total_sessions == 1,
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::TrainingConfig;
+
+// Grade bands from best to worst; every band above "Fail" is a pass
+pub const GRADE_BANDS: [&str; 5] = ["A", "B", "C", "Pass", FAIL_GRADE];
+pub const FAIL_GRADE: &str = "Fail";
+
+pub fn grade_rank(grade: &str) -> Option<usize> {
+    GRADE_BANDS.iter().position(|g| g.eq_ignore_ascii_case(grade))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GradeProbability {
+    pub grade: String,
+    pub probability: f64,
+}
+
+// Multinomial (softmax) logistic regression over `GRADE_BANDS`. Works on
+// inputs already standardized by the owning `TrainedModel`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeModel {
+    weights: Vec<Vec<f64>>, // One row per grade band
+    biases: Vec<f64>,
+    training_size: usize,
+}
+
+impl GradeModel {
+    // `labels` are indices into `GRADE_BANDS`
+    pub fn fit(scaled_rows: &[Vec<f64>], labels: &[usize], config: &TrainingConfig) -> Self {
+        let classes = GRADE_BANDS.len();
+        let dims = scaled_rows.first().map_or(0, |r| r.len());
+        let n = scaled_rows.len().max(1) as f64;
+
+        let mut weights = vec![vec![0.0; dims]; classes];
+        let mut biases = vec![0.0; classes];
+
+        for _ in 0..config.epochs {
+            let mut weight_grads = vec![vec![0.0; dims]; classes];
+            let mut bias_grads = vec![0.0; classes];
+
+            for (x, &label) in scaled_rows.iter().zip(labels) {
+                let probabilities = softmax(&logits(&weights, &biases, x));
+                for k in 0..classes {
+                    let error = probabilities[k] - if k == label { 1.0 } else { 0.0 };
+                    for j in 0..dims {
+                        weight_grads[k][j] += error * x[j];
+                    }
+                    bias_grads[k] += error;
+                }
+            }
+
+            for k in 0..classes {
+                for j in 0..dims {
+                    weights[k][j] -= config.learning_rate * (weight_grads[k][j] / n + config.l2_penalty * weights[k][j]);
+                }
+                biases[k] -= config.learning_rate * bias_grads[k] / n;
+            }
+        }
+
+        Self {
+            weights,
+            biases,
+            training_size: scaled_rows.len(),
+        }
+    }
+
+    pub fn training_size(&self) -> usize {
+        self.training_size
+    }
+
+    pub fn distribution(&self, scaled: &[f64]) -> Vec<GradeProbability> {
+        softmax(&logits(&self.weights, &self.biases, scaled))
+            .into_iter()
+            .zip(GRADE_BANDS)
+            .map(|(probability, grade)| GradeProbability {
+                grade: grade.to_string(),
+                probability,
+            })
+            .collect()
+    }
+}
+
+// Rescales a distribution so the passing bands together get
+// `pass_probability`, keeping their relative sizes, and "Fail" the rest
+pub fn with_pass_probability(distribution: &[GradeProbability], pass_probability: f64) -> Vec<GradeProbability> {
+    let passing: Vec<&GradeProbability> = distribution.iter().filter(|g| g.grade != FAIL_GRADE).collect();
+    let passing_mass: f64 = passing.iter().map(|g| g.probability).sum();
+
+    distribution
+        .iter()
+        .map(|g| GradeProbability {
+            grade: g.grade.clone(),
+            probability: if g.grade == FAIL_GRADE {
+                1.0 - pass_probability
+            } else if passing_mass > 0.0 {
+                g.probability / passing_mass * pass_probability
+            } else {
+                pass_probability / passing.len() as f64
+            },
+        })
+        .collect()
+}
+
+// The band to report with a Pass/Fail call: "Fail" for a predicted fail,
+// otherwise the most likely passing band
+pub fn grade_for(distribution: &[GradeProbability], passed: bool) -> String {
+    if !passed {
+        return FAIL_GRADE.to_string();
+    }
+    distribution
+        .iter()
+        .filter(|g| g.grade != FAIL_GRADE)
+        .max_by(|a, b| a.probability.total_cmp(&b.probability))
+        .map(|g| g.grade.clone())
+        .unwrap_or_else(|| "Pass".to_string())
+}
+
+// Probability of landing in `grade` or any better band
+pub fn probability_at_least(distribution: &[GradeProbability], grade: &str) -> Option<f64> {
+    let rank = grade_rank(grade)?;
+    Some(
+        distribution
+            .iter()
+            .filter(|g| grade_rank(&g.grade).is_some_and(|r| r <= rank))
+            .map(|g| g.probability)
+            .sum(),
+    )
+}
+
+fn logits(weights: &[Vec<f64>], biases: &[f64], x: &[f64]) -> Vec<f64> {
+    weights
+        .iter()
+        .zip(biases)
+        .map(|(w, b)| b + w.iter().zip(x).map(|(wi, xi)| wi * xi).sum::<f64>())
+        .collect()
+}
+
+fn softmax(logits: &[f64]) -> Vec<f64> {
+    let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = logits.iter().map(|z| (z - max).exp()).collect();
+    let total: f64 = exps.iter().sum();
+    exps.iter().map(|e| e / total).collect()
+}
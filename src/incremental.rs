@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data::Result;
+
+pub const MAX_INCREMENTAL_LEARNING_RATE: f64 = 1.0;
+pub const MAX_INCREMENTAL_EPOCHS: usize = 100;
+pub const MAX_WEIGHT_DRIFT: f64 = 10.0;
+
+// SGD settings for one incremental step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalOptions {
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    #[serde(default = "default_epochs")]
+    pub epochs: usize, // Passes over the new records
+    #[serde(default = "default_max_weight_drift")]
+    pub max_weight_drift: f64, // Cap on the distance from the base version's weights
+}
+
+fn default_learning_rate() -> f64 {
+    0.05
+}
+
+fn default_epochs() -> usize {
+    5
+}
+
+fn default_max_weight_drift() -> f64 {
+    1.0
+}
+
+impl IncrementalOptions {
+    // Rejects settings that would diverge, never finish or leave the weights
+    // uncapped. NaN is never in range
+    pub fn validate(&self) -> Result<()> {
+        if !(self.learning_rate > 0.0 && self.learning_rate <= MAX_INCREMENTAL_LEARNING_RATE) {
+            return Err(format!("learning_rate must be above 0 and at most {}", MAX_INCREMENTAL_LEARNING_RATE).into());
+        }
+        if !(1..=MAX_INCREMENTAL_EPOCHS).contains(&self.epochs) {
+            return Err(format!("epochs must be between 1 and {}", MAX_INCREMENTAL_EPOCHS).into());
+        }
+        if !(self.max_weight_drift > 0.0 && self.max_weight_drift <= MAX_WEIGHT_DRIFT) {
+            return Err(format!("max_weight_drift must be above 0 and at most {}", MAX_WEIGHT_DRIFT).into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for IncrementalOptions {
+    fn default() -> Self {
+        Self {
+            learning_rate: default_learning_rate(),
+            epochs: default_epochs(),
+            max_weight_drift: default_max_weight_drift(),
+        }
+    }
+}
+
+// Carried in the artifact of every incrementally updated version, so a chain
+// of steps stays anchored to the fully trained version it started from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalState {
+    pub base_version: String,
+    pub base_parameters: Vec<f64>, // Logistic weights then bias of the base version
+    pub steps: usize,
+    pub records_seen: usize,
+    pub weight_drift: f64, // Euclidean distance from `base_parameters`
+    pub last_outcome_at: Option<DateTime<Utc>>, // Newest outcome learned from
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalStep {
+    pub updated_from: String,
+    pub base_version: String,
+    pub step: usize,
+    pub records: usize,
+    pub weight_drift: f64,
+    pub capped: bool, // The update was pulled back to `max_weight_drift`
+    pub batch_accuracy_before: f64, // On the new records
+    pub batch_accuracy_after: f64,
+}
+
+// Pulls `parameters` back onto the sphere of radius `max_drift` around
+// `base` when they have moved further. Returns the parameters, their drift
+// and whether they were capped
+pub fn cap_drift(base: &[f64], parameters: &[f64], max_drift: f64) -> (Vec<f64>, f64, bool) {
+    let drift = distance(base, parameters);
+    if drift <= max_drift {
+        return (parameters.to_vec(), drift, false);
+    }
+
+    let scale = max_drift / drift;
+    let capped = base.iter().zip(parameters).map(|(b, p)| b + (p - b) * scale).collect();
+    (capped, max_drift, true)
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
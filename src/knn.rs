@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::model::{ModelInfo, PredictionExplanation, StudentFeatures, TrainingConfig};
+use crate::predictor::{family_info, occlusion_explanation, training_accuracy, ModelFamily, Predictor, Standardizer};
+
+// k-nearest neighbours over standardized features. The whole training set is
+// kept in the artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KNearestNeighbours {
+    k: usize,
+    standardizer: Standardizer,
+    rows: Vec<Vec<f64>>, // Standardized
+    labels: Vec<bool>,
+    training_accuracy: f64,
+    #[serde(default)]
+    trained_at: String,
+}
+
+impl KNearestNeighbours {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        if records.is_empty() {
+            return Err("Cannot train a model without records".into());
+        }
+
+        let raw: Vec<Vec<f64>> = records.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+        let standardizer = Standardizer::fit_with(&raw, config.preprocessing.scaling);
+
+        let mut model = Self {
+            k: config.k_neighbours.clamp(1, records.len()),
+            rows: raw.iter().map(|r| standardizer.transform(r)).collect(),
+            standardizer,
+            labels: records.iter().map(|r| r.passed()).collect(),
+            training_accuracy: 0.0,
+            trained_at: chrono::Utc::now().to_rfc3339(),
+        };
+        model.training_accuracy = training_accuracy(&model, records);
+
+        Ok(model)
+    }
+
+    // Indices of the k closest training rows, nearest first. Ties keep
+    // training order so results are deterministic
+    pub fn neighbours(&self, features: &StudentFeatures) -> Vec<usize> {
+        let query = self.standardizer.transform(&features.to_vector());
+        let mut distances: Vec<(usize, f64)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (i, row.iter().zip(&query).map(|(a, b)| (a - b).powi(2)).sum::<f64>()))
+            .collect();
+        distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        distances.into_iter().take(self.k).map(|(i, _)| i).collect()
+    }
+}
+
+impl Predictor for KNearestNeighbours {
+    // Share of passing neighbours, smoothed towards 0.5 so small k never
+    // gives a certain answer
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        let neighbours = self.neighbours(features);
+        let passes = neighbours.iter().filter(|&&i| self.labels[i]).count();
+        (passes as f64 + 1.0) / (neighbours.len() as f64 + 2.0)
+    }
+
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        occlusion_explanation(self, features, &self.standardizer.means)
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        family_info(ModelFamily::KNearestNeighbours, self.rows.len(), self.training_accuracy, &self.trained_at)
+    }
+}
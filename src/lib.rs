@@ -0,0 +1,27 @@
+pub mod config;
+pub mod data;
+pub mod model;
+pub mod predictor;
+pub mod logistic;
+pub mod tree;
+pub mod boosting;
+pub mod knn;
+pub mod calibration;
+pub mod bootstrap;
+pub mod threshold;
+pub mod search;
+pub mod fairness;
+pub mod drift;
+pub mod retrain;
+pub mod shadow;
+pub mod peers;
+pub mod incremental;
+pub mod preprocessing;
+pub mod forecast;
+pub mod grades;
+pub mod counterfactual;
+pub mod evaluation;
+pub mod analytics;
+pub mod database;
+pub mod gamification;
+pub mod registry;
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::model::{FeatureContribution, ModelInfo, PredictionExplanation, StudentFeatures, TrainingConfig, FEATURE_NAMES};
+use crate::predictor::{family_info, imputed_flags, sigmoid, training_accuracy, ModelFamily, Predictor, Standardizer};
+
+// Logistic regression fitted with batch gradient descent on standardized features
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticRegression {
+    weights: Vec<f64>,
+    bias: f64,
+    standardizer: Standardizer,
+    training_size: usize,
+    training_accuracy: f64,
+    #[serde(default)]
+    trained_at: String,
+}
+
+impl LogisticRegression {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        if records.is_empty() {
+            return Err("Cannot train a model without records".into());
+        }
+
+        let rows: Vec<Vec<f64>> = records.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+        let labels: Vec<f64> = records.iter().map(|r| if r.passed() { 1.0 } else { 0.0 }).collect();
+        let n = rows.len() as f64;
+        let dims = FEATURE_NAMES.len();
+
+        let standardizer = Standardizer::fit_with(&rows, config.preprocessing.scaling);
+        let scaled: Vec<Vec<f64>> = rows.iter().map(|r| standardizer.transform(r)).collect();
+
+        let mut weights = vec![0.0; dims];
+        let mut bias = 0.0;
+
+        for _ in 0..config.epochs {
+            let mut weight_grads = vec![0.0; dims];
+            let mut bias_grad = 0.0;
+
+            for (x, y) in scaled.iter().zip(&labels) {
+                let z = bias + x.iter().zip(&weights).map(|(xi, wi)| xi * wi).sum::<f64>();
+                let error = sigmoid(z) - y;
+                for j in 0..dims {
+                    weight_grads[j] += error * x[j];
+                }
+                bias_grad += error;
+            }
+
+            for j in 0..dims {
+                weights[j] -= config.learning_rate * (weight_grads[j] / n + config.l2_penalty * weights[j]);
+            }
+            bias -= config.learning_rate * bias_grad / n;
+        }
+
+        let mut model = Self {
+            weights,
+            bias,
+            standardizer,
+            training_size: records.len(),
+            training_accuracy: 0.0,
+            trained_at: chrono::Utc::now().to_rfc3339(),
+        };
+        model.training_accuracy = training_accuracy(&model, records);
+
+        Ok(model)
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.weights.len()
+    }
+
+    // Weights followed by the bias
+    pub fn parameters(&self) -> Vec<f64> {
+        let mut parameters = self.weights.clone();
+        parameters.push(self.bias);
+        parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: &[f64]) {
+        let (bias, weights) = parameters.split_last().expect("parameters include the bias");
+        self.weights = weights.to_vec();
+        self.bias = *bias;
+    }
+
+    // Stochastic gradient descent, one record at a time in the given order.
+    // The standardizer stays as trained so old and new weights stay comparable
+    pub fn sgd_update(&mut self, records: &[TrainingRecord], learning_rate: f64, l2_penalty: f64, epochs: usize) {
+        for _ in 0..epochs {
+            for record in records {
+                let x = self.standardizer.transform(&StudentFeatures::from(record).to_vector());
+                let y = if record.passed() { 1.0 } else { 0.0 };
+                let z = self.bias + x.iter().zip(&self.weights).map(|(xi, wi)| xi * wi).sum::<f64>();
+                let error = sigmoid(z) - y;
+
+                for (w, xi) in self.weights.iter_mut().zip(&x) {
+                    *w -= learning_rate * (error * xi + l2_penalty * *w);
+                }
+                self.bias -= learning_rate * error;
+            }
+        }
+        self.training_size += records.len();
+        self.trained_at = chrono::Utc::now().to_rfc3339();
+    }
+}
+
+impl Predictor for LogisticRegression {
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        let scaled = self.standardizer.transform(&features.to_vector());
+        sigmoid(self.bias + scaled.iter().zip(&self.weights).map(|(x, w)| x * w).sum::<f64>())
+    }
+
+    // Exact breakdown: the model's own weights on the standardized inputs,
+    // which add up to the prediction's log-odds together with the bias
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        let vector = features.to_vector();
+        let scaled = self.standardizer.transform(&vector);
+        let imputed = imputed_flags(features);
+
+        let mut contributions: Vec<FeatureContribution> = (0..vector.len())
+            .map(|j| FeatureContribution {
+                feature: FEATURE_NAMES[j].to_string(),
+                value: vector[j],
+                contribution: self.weights[j] * scaled[j],
+                imputed: imputed[j],
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+        PredictionExplanation {
+            baseline_log_odds: self.bias,
+            contributions,
+        }
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        family_info(ModelFamily::LogisticRegression, self.training_size, self.training_accuracy, &self.trained_at)
+    }
+}
@@ -1,40 +1,200 @@
-mod data;
-mod model;
-mod analytics;
-mod database;
-mod gamification;
-
 use actix_web::{web, App, HttpResponse, HttpServer};
 use serde::Deserialize;
-use rand::Rng;
-
-use crate::model::{train_model, ModelInfo, PredictResponse, AnalyticsData, PerformanceCategory, 
-                   StudentRecord as ModelStudentRecord, TrainedModel, StudyPlanRequest};
-use crate::analytics::{TrendsAnalyzer, generate_mock_trends_data};
-use crate::database::{Database, StudentRecord as DbStudentRecord, ModelVersion};
-use crate::gamification::{
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use student_classifier::config::AppConfig;
+use student_classifier::counterfactual::{find_counterfactual, FeatureBounds};
+use student_classifier::data::{load_dataset, parse_dataset, stratified_train_test_split};
+use student_classifier::evaluation::{cross_validate, evaluate_model, DEFAULT_CV_FOLDS};
+use student_classifier::drift::{monitor_drift, MAX_DRIFT_WINDOWS, MAX_DRIFT_WINDOW_SIZE, MIN_DRIFT_SAMPLE};
+use student_classifier::fairness::fairness_audit;
+use student_classifier::grades::{grade_for, grade_rank};
+use student_classifier::model::{load_or_train_model, train_model_with_config, train_validated_model, PredictResponse, AnalyticsData, PerformanceCategory, 
+                   StudentRecord as ModelStudentRecord, StudentFeatures, StudyPlanRequest,
+                   UncertaintyOptions};
+use student_classifier::predictor::Predictor;
+use student_classifier::analytics::{student_histories, TrendsAnalyzer};
+use student_classifier::database::{Database, ShadowPrediction, ANONYMOUS_STUDENT, StudentOutcome, StudentRecord as DbStudentRecord, TrendFilter, WeeklyRecord};
+use student_classifier::registry::{parse_artifact, ModelRegistry, RegistryError};
+use student_classifier::forecast::{forecast_risk, validate_forecast, WeeklyObservation, DEFAULT_FORECAST_WEEKS};
+use student_classifier::incremental::IncrementalOptions;
+use student_classifier::peers::{PeerDirectory, PeerIndex, DEFAULT_PEER_COUNT};
+use student_classifier::retrain::{outcome_records, retrain_candidate, RetrainOptions};
+use student_classifier::shadow::shadow_report;
+use student_classifier::search::{hyperparameter_search, SearchOptions, SearchSpace};
+use student_classifier::threshold::ThresholdObjective;
+use student_classifier::gamification::{
     GamificationEngine, StudySessionRequest, 
     GamificationResponse, get_mock_leaderboard, get_mock_profile
 };
 
 #[derive(Deserialize)]
 struct PredictRequest {
-    hours: f64,
-    attendance: f64,
+    #[serde(flatten)]
+    features: StudentFeatures,
+    #[serde(default)]
+    uncertainty: Option<UncertaintyOptions>,
+    #[serde(default)]
+    explain: bool, // Include per-feature contributions
+    #[serde(default)]
+    similar_students: Option<usize>, // Include this many similar past students
+}
+
+// "Students like you" lookup
+#[derive(Deserialize)]
+struct SimilarStudentsRequest {
+    #[serde(flatten)]
+    features: StudentFeatures,
+    #[serde(default = "default_peer_count")]
+    k: usize,
+}
+
+fn default_peer_count() -> usize {
+    DEFAULT_PEER_COUNT
+}
+
+#[derive(Deserialize)]
+struct BatchPredictQuery {
+    #[serde(default)]
+    explain: bool,
+}
+
+// Evaluation (and fairness audit) request: score `csv` when given, otherwise
+// a seeded hold-out split
+#[derive(Deserialize)]
+struct EvaluateRequest {
+    #[serde(default)]
+    version: Option<String>, // Defaults to the active version
+    #[serde(default)]
+    csv: Option<String>,
+    #[serde(default = "default_test_fraction")]
+    test_fraction: f64,
+    #[serde(default = "default_split_seed")]
+    seed: u64,
+}
+
+// Fairness audit request: the attribute columns to compare groups on, and
+// the data to score as for /evaluate
+#[derive(Deserialize)]
+struct FairnessRequest {
+    attributes: Vec<String>,
+    #[serde(flatten)]
+    data: EvaluateRequest,
+}
+
+// Cross-validation request for the active version's training configuration
+#[derive(Deserialize)]
+struct CrossValidateRequest {
+    #[serde(default = "default_cv_folds")]
+    k: usize,
+    #[serde(default = "default_split_seed")]
+    seed: u64,
+}
+
+// Hyperparameter search over `space` (the default grid when omitted)
+#[derive(Deserialize)]
+struct HyperparameterSearchRequest {
+    #[serde(default)]
+    space: SearchSpace,
+    #[serde(flatten)]
+    options: SearchOptions,
+}
+
+// The true result for a student whose prediction was stored
+#[derive(Deserialize)]
+struct OutcomeRequest {
+    name: String,
+    passed: bool,
+    #[serde(default)]
+    grade: Option<String>,
+}
+
+// One SGD step on outcomes recorded since `version` (default: the active
+// version) last learned from them
+#[derive(Deserialize)]
+struct IncrementalUpdateRequest {
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    activate: bool, // Serve the checkpoint straight away
+    #[serde(flatten)]
+    options: IncrementalOptions,
+}
+
+#[derive(Deserialize)]
+struct ShadowReportQuery {
+    #[serde(default)]
+    version: Option<String>,
+}
+
+// Drift monitoring over the `windows` most recent windows of stored predictions
+#[derive(Deserialize)]
+struct DriftQuery {
+    #[serde(default = "default_drift_window_size")]
+    window_size: usize,
+    #[serde(default = "default_drift_windows")]
+    windows: usize,
+}
+
+fn default_drift_window_size() -> usize {
+    50
+}
+
+fn default_drift_windows() -> usize {
+    4
+}
+
+fn default_cv_folds() -> usize {
+    DEFAULT_CV_FOLDS
+}
+
+fn default_test_fraction() -> f64 {
+    0.3
+}
+
+fn default_split_seed() -> u64 {
+    42
+}
+
+// "What would it take to pass" request; the target defaults to the active
+// model's Pass/Fail cutoff
+#[derive(Deserialize)]
+struct CounterfactualRequest {
+    #[serde(flatten)]
+    features: StudentFeatures,
+    #[serde(default)]
+    target_probability: Option<f64>,
+    #[serde(default)]
+    bounds: FeatureBounds,
 }
 
 // Student trends request
 #[derive(Deserialize)]
 struct StudentTrendsRequest {
     student_name: String,
-    weekly_data: Vec<WeeklyData>,
+    weekly_data: Vec<WeeklyObservation>,
+    #[serde(default = "default_forecast_weeks")]
+    weeks_ahead: usize, // Weeks left in the term to project
+    #[serde(default)]
+    previous_grades: Option<f64>,
+    #[serde(default)]
+    sleep_hours: Option<f64>,
 }
 
+fn default_forecast_weeks() -> usize {
+    DEFAULT_FORECAST_WEEKS
+}
+
+// A student's weekly habits in a course, stored for class trends
 #[derive(Deserialize)]
-struct WeeklyData {
-    week: usize,
-    study_hours: f64,
-    attendance: f64,
+struct WeeklyRecordsRequest {
+    student_name: String,
+    #[serde(default)]
+    course: Option<String>,
+    #[serde(default)]
+    cohort: Option<String>,
+    weekly_data: Vec<WeeklyObservation>,
 }
 
 // Student progress tracking request
@@ -42,41 +202,123 @@ struct WeeklyData {
 struct ProgressRequest {
     student_name: String,
     weeks: usize,
+    #[serde(default)]
+    seed: Option<u64>, // Add reproducible week-to-week variation
+}
+
+// Cross-validation endpoint: per-fold metrics and their spread on the
+// dataset. The refits run on the blocking pool, as for the search
+async fn cross_validate_model(
+    req: web::Json<CrossValidateRequest>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = registry.active_version();
+    let training_config = registry.current().training().config.clone();
+
+    let (data_path, k, seed) = (config.data_path.clone(), req.k, req.seed);
+    let outcome = web::block(move || {
+        load_dataset(&data_path)
+            .and_then(|records| cross_validate(&records, &training_config, k, seed))
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let report = match outcome {
+        Ok(Ok(report)) => report,
+        Ok(Err(e)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Cross-validation failed: {}", e)
+            }))
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Cross-validation failed: {}", e)
+            }))
+        }
+    };
+
+    match serde_json::to_value(&report) {
+        Ok(stored) => {
+            if let Err(e) = db.save_model_report(&version, "cross_validation", &stored).await {
+                eprintln!("Failed to save cross-validation for {}: {}", version, e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize cross-validation report: {}", e),
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "version": version,
+        "report": report,
+    }))
 }
 
 // Student trends endpoint
 async fn get_student_trends(
     req: web::Json<StudentTrendsRequest>,
+    registry: web::Data<ModelRegistry>,
 ) -> HttpResponse {
+    if let Err(e) = validate_forecast(&req.weekly_data, req.weeks_ahead) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e.to_string() }));
+    }
+
     let analyzer = TrendsAnalyzer::new();
     
-    let historical_data: Vec<(f64, f64)> = req.weekly_data
+    let mut weekly_data: Vec<WeeklyObservation> = req.weekly_data.clone();
+    weekly_data.sort_by_key(|wd| wd.week);
+
+    let historical_data: Vec<(f64, f64)> = weekly_data
         .iter()
         .map(|wd| (wd.study_hours, wd.attendance))
         .collect();
 
-    let trend = analyzer.generate_student_trend(&req.student_name, historical_data);
+    let mut trend = analyzer.generate_student_trend(&req.student_name, historical_data);
+    // Needs at least two distinct weeks
+    trend.forecast = forecast_risk(
+        registry.current().as_ref(),
+        &weekly_data,
+        req.weeks_ahead,
+        req.previous_grades,
+        req.sleep_hours,
+    );
     HttpResponse::Ok().json(trend)
 }
 
 // Class trends endpoint
-async fn get_class_trends() -> HttpResponse {
-    let analyzer = TrendsAnalyzer::new();
-    let mock_data = generate_mock_trends_data();
-    let class_trends = analyzer.generate_class_trends(mock_data);
-    HttpResponse::Ok().json(class_trends)
+async fn get_class_trends(
+    query: web::Query<TrendFilter>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    match db.get_weekly_records(&query).await {
+        Ok(records) => HttpResponse::Ok().json(TrendsAnalyzer::new().generate_class_trends(&records)),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Database error: {}", e)
+        })),
+    }
 }
 
 // Trends dashboard endpoint
-async fn get_trends_dashboard() -> HttpResponse {
+async fn get_trends_dashboard(
+    query: web::Query<TrendFilter>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let records = match db.get_weekly_records(&query).await {
+        Ok(records) => records,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Database error: {}", e)
+            }))
+        }
+    };
+
     let analyzer = TrendsAnalyzer::new();
-    let mock_data = generate_mock_trends_data();
-    
-    let class_trends = analyzer.generate_class_trends(mock_data.clone());
+    let class_trends = analyzer.generate_class_trends(&records);
     
     let mut student_trends = Vec::new();
-    for (student_name, data) in mock_data.iter().take(3) {
-        let trend = analyzer.generate_student_trend(student_name, data.clone());
+    for ((student_name, course), data) in student_histories(&records) {
+        let mut trend = analyzer.generate_student_trend(&student_name, data);
+        trend.course = course;
         student_trends.push(trend);
     }
 
@@ -89,13 +331,53 @@ async fn get_trends_dashboard() -> HttpResponse {
     HttpResponse::Ok().json(dashboard_data)
 }
 
+// Store a student's weekly habits, each week scored by the active model, so
+// class trends reflect them. Either every week is saved or none is
+async fn record_weekly_history(
+    req: web::Json<WeeklyRecordsRequest>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(e) = req.weekly_data.iter().try_for_each(WeeklyObservation::validate) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e.to_string()}));
+    }
+    let model = registry.current();
+    let records: Vec<WeeklyRecord> = req.weekly_data.iter().map(|week| {
+        let features = StudentFeatures::new(week.study_hours, week.attendance);
+        WeeklyRecord {
+            id: 0,
+            student_name: req.student_name.clone(),
+            course: req.course.clone(),
+            cohort: req.cohort.clone(),
+            week: week.week,
+            study_hours: week.study_hours,
+            attendance: week.attendance,
+            predicted_pass: model.predict(&features).0,
+            confidence: model.predict_proba(&features),
+            recorded_at: chrono::Utc::now(),
+        }
+    }).collect();
+
+    match db.save_weekly_records(&records).await {
+        Ok(saved) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Weekly history recorded",
+            "student_name": req.student_name,
+            "weeks_recorded": saved,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to save weekly history for {}: {}", req.student_name, e)
+        })),
+    }
+}
+
 // Student progress tracking endpoint
 async fn track_student_progress(
     req: web::Json<ProgressRequest>,
-    model: web::Data<TrainedModel>,
+    registry: web::Data<ModelRegistry>,
 ) -> HttpResponse {
+    let model = registry.current();
     let mut progress_data = Vec::new();
-    let mut rng = rand::thread_rng();
+    let mut rng = req.seed.map(StdRng::seed_from_u64);
     
     // Generate simulated progress data
     for week in 1..=req.weeks {
@@ -103,12 +385,17 @@ async fn track_student_progress(
         let base_hours = 4.0 + (week as f64 * 0.5);
         let base_attendance = 70.0 + (week as f64 * 2.0);
         
-        // Add some randomness
-        let hours = (base_hours + rng.gen_range(-1.0..1.0)).max(0.0);
-        let attendance = (base_attendance + rng.gen_range(-5.0..5.0)).clamp(0.0, 100.0);
+        // Add some randomness only when a seed was supplied
+        let (hours_noise, attendance_noise) = match rng.as_mut() {
+            Some(rng) => (rng.gen_range(-1.0..1.0), rng.gen_range(-5.0..5.0)),
+            None => (0.0, 0.0),
+        };
+        let hours = (base_hours + hours_noise).max(0.0);
+        let attendance = (base_attendance + attendance_noise).clamp(0.0, 100.0);
         
-        let features = vec![hours, attendance];
-        let (prediction, confidence) = model.predict(&features);
+        let features = StudentFeatures::new(hours, attendance);
+        let (prediction, _) = model.predict(&features);
+        let confidence = model.predict_proba(&features);
         
         progress_data.push(serde_json::json!({
             "week": week,
@@ -151,8 +438,17 @@ fn analyze_progress_trend(progress_data: &[serde_json::Value]) -> String {
 // Study Plan Generator endpoint
 async fn generate_study_plan(
     req: web::Json<StudyPlanRequest>,
-    model: web::Data<TrainedModel>,
+    registry: web::Data<ModelRegistry>,
 ) -> HttpResponse {
+    // Targets come from the same grid search as /counterfactual
+    let current = StudentFeatures::new(req.current_hours, req.current_attendance);
+    if let Err(e) = FeatureBounds::default().validate(&current) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        }));
+    }
+
+    let model = registry.current();
     let study_plan = model.generate_study_plan(&req);
     HttpResponse::Ok().json(study_plan)
 }
@@ -160,63 +456,146 @@ async fn generate_study_plan(
 // Prediction endpoint with database
 async fn predict(
     req: web::Json<PredictRequest>,
-    model: web::Data<TrainedModel>,
+    registry: web::Data<ModelRegistry>,
+    peers: web::Data<PeerDirectory>,
     db: web::Data<Database>,
 ) -> HttpResponse {
-    let features = vec![req.hours, req.attendance];
-    let (prediction, confidence) = model.predict(&features);
+    if let Some(Err(e)) = req.uncertainty.as_ref().map(UncertaintyOptions::validate) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e.to_string() }));
+    }
+
+    let model = registry.current();
+    let features = &req.features;
+    let (prediction, _) = model.predict(features);
+    let confidence = model.predict_proba(features);
+    let grade_distribution = model.grade_distribution(features);
+    let predicted_grade = grade_distribution.as_deref().map(|d| grade_for(d, prediction));
     
     // Save to database
     let record = DbStudentRecord {
         id: 0,
-        name: "Anonymous Student".to_string(),
-        study_hours: req.hours,
-        attendance: req.attendance,
+        name: ANONYMOUS_STUDENT.to_string(),
+        study_hours: features.hours,
+        attendance: features.attendance,
+        previous_grades: features.previous_grades,
+        sleep_hours: features.sleep_hours,
         predicted_pass: prediction,
         confidence,
+        predicted_grade: predicted_grade.clone(),
+        model_version: Some(registry.active_version()),
         created_at: chrono::Utc::now(),
     };
 
-    if let Err(e) = db.save_prediction(&record).await {
-        eprintln!("Failed to save prediction: {}", e);
+    match db.save_prediction(&record).await {
+        Ok(prediction_id) => shadow_predict(&registry, &db, prediction_id, features).await,
+        Err(e) => eprintln!("Failed to save prediction: {}", e),
     }
 
+    let uncertainty = req.uncertainty
+        .as_ref()
+        .map(|options| model.predict_with_uncertainty(features, options));
+
     let response = PredictResponse {
         prediction: if prediction { "Pass".to_string() } else { "Fail".to_string() },
         confidence,
+        uncertainty,
+        explanation: if req.explain { Some(model.explain(features)) } else { None },
+        predicted_grade,
+        grade_distribution,
+        similar_students: req.similar_students.map(|k| peers.current().nearest(features, k)),
+        review: model.review(features),
     };
     
     HttpResponse::Ok().json(response)
 }
 
+// The k most similar past students (dataset and recorded outcomes) with
+// their results, anonymised
+async fn similar_students(
+    req: web::Json<SimilarStudentsRequest>,
+    peers: web::Data<PeerDirectory>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(peers.current().nearest(&req.features, req.k))
+}
+
+// Counterfactual endpoint: smallest change to hours/attendance that reaches the target
+async fn counterfactual(
+    req: web::Json<CounterfactualRequest>,
+    registry: web::Data<ModelRegistry>,
+) -> HttpResponse {
+    let model = registry.current();
+    let target_probability = req.target_probability.unwrap_or_else(|| model.decision_threshold());
+    if !(0.0..1.0).contains(&target_probability) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "target_probability must be at least 0 and below 1"
+        }));
+    }
+    if let Err(e) = req.bounds.validate(&req.features) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        }));
+    }
+
+    let result = find_counterfactual(&model, &req.features, target_probability, &req.bounds);
+    HttpResponse::Ok().json(result)
+}
+
 // Batch prediction endpoint with database
 async fn batch_predict(
     students: web::Json<Vec<ModelStudentRecord>>,
-    model: web::Data<TrainedModel>,
+    query: web::Query<BatchPredictQuery>,
+    registry: web::Data<ModelRegistry>,
     db: web::Data<Database>,
 ) -> HttpResponse {
-    let batch_result = model.batch_predict(students.into_inner());
+    let model = registry.current();
+    let model_version = registry.active_version();
+    let batch_result = model.batch_predict(students.into_inner(), query.explain);
     
     // Save batch predictions to database
     for student in &batch_result.predictions {
         let record = DbStudentRecord {
             id: 0,
             name: student.name.clone(),
-            study_hours: student.hours,
-            attendance: student.attendance,
+            study_hours: student.features.hours,
+            attendance: student.features.attendance,
+            previous_grades: student.features.previous_grades,
+            sleep_hours: student.features.sleep_hours,
             predicted_pass: student.prediction == "Pass",
             confidence: student.confidence,
+            predicted_grade: student.predicted_grade.clone(),
+            model_version: Some(model_version.clone()),
             created_at: chrono::Utc::now(),
         };
 
-        if let Err(e) = db.save_prediction(&record).await {
-            eprintln!("Failed to save batch prediction for {}: {}", student.name, e);
+        match db.save_prediction(&record).await {
+            Ok(prediction_id) => shadow_predict(&registry, &db, prediction_id, &student.features).await,
+            Err(e) => eprintln!("Failed to save batch prediction for {}: {}", student.name, e),
         }
     }
     
     HttpResponse::Ok().json(batch_result)
 }
 
+// Score a stored prediction with the challenger, if one is shadowing, and
+// store its output without affecting the response
+async fn shadow_predict(registry: &ModelRegistry, db: &Database, prediction_id: i64, features: &StudentFeatures) {
+    let Some((version, challenger)) = registry.challenger() else {
+        return;
+    };
+
+    let shadow = ShadowPrediction {
+        id: 0,
+        prediction_id,
+        model_version: version,
+        predicted_pass: challenger.predict(features).0,
+        confidence: challenger.predict_proba(features),
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = db.save_shadow_prediction(&shadow).await {
+        eprintln!("Failed to save shadow prediction from {}: {}", shadow.model_version, e);
+    }
+}
+
 // Analytics endpoint
 async fn get_analytics() -> HttpResponse {
     let analytics = AnalyticsData {
@@ -253,24 +632,39 @@ async fn get_analytics() -> HttpResponse {
 
 // Database statistics endpoint
 async fn get_database_analytics(db: web::Data<Database>) -> HttpResponse {
-    match db.get_class_statistics().await {
-        Ok(stats) => {
+    let stats = db.get_class_statistics().await;
+    let grades = db.get_grade_breakdown().await;
+
+    match (stats, grades) {
+        (Ok(stats), Ok(mut grades)) => {
+            // Breakdown by predicted grade band, best band first
+            grades.sort_by_key(|g| grade_rank(&g.grade).unwrap_or(usize::MAX));
+            let mut performance_breakdown: Vec<PerformanceCategory> = grades
+                .into_iter()
+                .map(|g| PerformanceCategory {
+                    range: format!("Predicted {}", g.grade),
+                    count: g.count as usize,
+                    pass_rate: g.pass_rate,
+                })
+                .collect();
+            if performance_breakdown.is_empty() {
+                performance_breakdown.push(PerformanceCategory {
+                    range: "Real Data".to_string(),
+                    count: stats.total_students as usize,
+                    pass_rate: stats.pass_rate,
+                });
+            }
+
             let analytics = AnalyticsData {
                 total_students: stats.total_students as usize,
                 pass_rate: stats.pass_rate,
                 avg_study_hours: stats.avg_study_hours,
                 avg_attendance: stats.avg_attendance,
-                performance_breakdown: vec![
-                    PerformanceCategory {
-                        range: "Real Data".to_string(),
-                        count: stats.total_students as usize,
-                        pass_rate: stats.pass_rate,
-                    },
-                ],
+                performance_breakdown,
             };
             HttpResponse::Ok().json(analytics)
         },
-        Err(e) => {
+        (Err(e), _) | (_, Err(e)) => {
             HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": format!("Database error: {}", e)
             }))
@@ -279,35 +673,36 @@ async fn get_database_analytics(db: web::Data<Database>) -> HttpResponse {
 }
 
 // Real trends from database
-async fn get_real_trends_dashboard(db: web::Data<Database>) -> HttpResponse {
-    match db.get_weekly_trends().await {
-        Ok(weekly_trends) => {
+async fn get_real_trends_dashboard(
+    query: web::Query<TrendFilter>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let filter = query.into_inner();
+    let loaded = match db.get_weekly_trends(&filter).await {
+        Ok(weekly_trends) => db.get_weekly_records(&filter).await.map(|records| (weekly_trends, records)),
+        Err(e) => Err(e),
+    };
+
+    match loaded {
+        Ok((weekly_trends, records)) => {
             let analyzer = TrendsAnalyzer::new();
-            
-            // Convert database trends to analytics format
-            let mut student_trends = Vec::new();
-            
-            // For real trends, we'll use the weekly aggregated data
-            for (_index, trend) in weekly_trends.iter().enumerate() {
-                let student_name = format!("Week {}", trend.week);
-                let historical_data = vec![
-                    (trend.avg_study_hours, trend.avg_attendance)
-                ];
-                let student_trend = analyzer.generate_student_trend(&student_name, historical_data);
-                student_trends.push(student_trend);
-            }
-            
-            // Generate mock class trends for now since we need individual student data
-            let mock_data = generate_mock_trends_data();
-            let class_trends = analyzer.generate_class_trends(mock_data);
-            
+            let student_trends: Vec<_> = student_histories(&records)
+                .into_iter()
+                .map(|((student_name, course), history)| {
+                    let mut trend = analyzer.generate_student_trend(&student_name, history);
+                    trend.course = course;
+                    trend
+                })
+                .collect();
+
             let dashboard_data = serde_json::json!({
-                "class_trends": class_trends,
+                "class_trends": analyzer.generate_class_trends(&records),
                 "student_trends": student_trends,
                 "weekly_trends": weekly_trends,
+                "filter": filter,
                 "timestamp": chrono::Utc::now().to_rfc3339(),
                 "data_source": "real_database",
-                "total_records": weekly_trends.len(),
+                "total_records": records.len(),
             });
 
             HttpResponse::Ok().json(dashboard_data)
@@ -332,35 +727,623 @@ async fn get_all_predictions(db: web::Data<Database>) -> HttpResponse {
     }
 }
 
-// Save model version to database
+// Retrain from the dataset and register the result as a new, inactive model version
 async fn save_model_version(
-    model_info: web::Data<ModelInfo>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
     db: web::Data<Database>,
 ) -> HttpResponse {
-    let model_version = ModelVersion {
-        id: 0,
-        version: "1.0.0".to_string(),
-        accuracy: model_info.accuracy,
-        features_used: "study_hours,attendance".to_string(),
-        created_at: chrono::Utc::now(),
+    let model = match train_model_with_config(&config.data_path, &config.training_config()) {
+        Ok(model) => model,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to train model: {}", e)
+            }))
+        }
     };
 
-    match db.save_model_version(&model_version).await {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+    match registry.register(&db, &model).await {
+        Ok(model_version) => HttpResponse::Ok().json(serde_json::json!({
             "message": "Model version saved successfully",
             "version": model_version
         })),
+        Err(e) => registry_error_response(e),
+    }
+}
+
+// Search the hyperparameter space on the dataset, then train the best
+// configuration and register it as a new, inactive model version with the
+// leaderboard stored next to it. The refitting runs on the blocking pool so
+// a large search does not stall the worker serving other requests
+async fn search_hyperparameters(
+    req: web::Json<HyperparameterSearchRequest>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let req = req.into_inner();
+    let (data_path, base) = (config.data_path.clone(), config.training_config());
+    let (space, options) = (req.space.clone(), req.options.clone());
+    let outcome = web::block(move || {
+        load_dataset(&data_path)
+            .and_then(|records| {
+                let leaderboard = hyperparameter_search(&records, &base, &space, &options)?;
+                let model = train_validated_model(&records, &leaderboard[0].config)?;
+                Ok((leaderboard, model))
+            })
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let (leaderboard, model) = match outcome {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Hyperparameter search failed: {}", e)
+            }))
+        }
         Err(e) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to save model version: {}", e)
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Hyperparameter search failed: {}", e)
+            }))
+        }
+    };
+
+    let model_version = match registry.register(&db, &model).await {
+        Ok(model_version) => model_version,
+        Err(e) => return registry_error_response(e),
+    };
+
+    let stored = serde_json::json!({ "options": req.options, "space": req.space, "leaderboard": leaderboard });
+    if let Err(e) = db.save_model_report(&model_version.version, "hyperparameter_search", &stored).await {
+        eprintln!("Failed to save hyperparameter search for {}: {}", model_version.version, e);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Best configuration saved as a new model version",
+        "version": model_version,
+        "leaderboard": leaderboard,
+    }))
+}
+
+fn registry_error_response(error: RegistryError) -> HttpResponse {
+    let body = serde_json::json!({ "error": error.to_string() });
+    match error {
+        RegistryError::NotFound(_) => HttpResponse::NotFound().json(body),
+        RegistryError::NoPreviousVersion | RegistryError::AlreadyActive(_) => HttpResponse::Conflict().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+// Model registry endpoints
+async fn list_model_versions(db: web::Data<Database>) -> HttpResponse {
+    match db.list_model_versions().await {
+        Ok(versions) => HttpResponse::Ok().json(versions),
+        Err(e) => registry_error_response(e.into()),
+    }
+}
+
+async fn get_model_version(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = path.into_inner();
+    let record = match db.get_model_version(&version).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return registry_error_response(RegistryError::NotFound(version)),
+        Err(e) => return registry_error_response(e.into()),
+    };
+
+    let model = match parse_artifact(&record) {
+        Ok(model) => model,
+        Err(e) => return registry_error_response(e),
+    };
+
+    match db.get_model_reports(&version).await {
+        Ok(reports) => HttpResponse::Ok().json(serde_json::json!({
+            "version": record,
+            "model_info": model.metadata(),
+            "training": model.training(),
+            "reports": reports,
+        })),
+        Err(e) => registry_error_response(e.into()),
+    }
+}
+
+// Evaluate a model version on uploaded labelled data or a hold-out split.
+// For the hold-out, the version's training config is refitted (and
+// recalibrated) on the training side so the metrics are measured on rows the
+// model has not seen. Those metrics describe the refit, not the stored
+// artifact, so they are stored as a separate "refit_evaluation" report. The
+// scoring runs on the blocking pool, as for the search
+async fn evaluate(
+    req: web::Json<EvaluateRequest>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = req.version.clone().unwrap_or_else(|| registry.active_version());
+    let model = match db.get_model_version(&version).await {
+        Ok(Some(record)) => match parse_artifact(&record) {
+            Ok(model) => model,
+            Err(e) => return registry_error_response(e),
+        },
+        Ok(None) => return registry_error_response(RegistryError::NotFound(version)),
+        Err(e) => return registry_error_response(e.into()),
+    };
+
+    let (csv, data_path, test_fraction, seed) = (req.csv.clone(), config.data_path.clone(), req.test_fraction, req.seed);
+    let outcome = web::block(move || {
+        match csv {
+            Some(csv) => parse_dataset(&csv).map(|records| ("evaluation", "uploaded_csv", evaluate_model(&model, &records))),
+            None => load_dataset(&data_path).and_then(|records| {
+                let (train, test) = stratified_train_test_split(&records, test_fraction, seed);
+                let holdout_model = train_validated_model(&train, &model.training().config)?;
+                Ok(("refit_evaluation", "holdout_refit", evaluate_model(&holdout_model, &test)))
+            }),
+        }
+        .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let (kind, source, report) = match outcome {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Evaluation failed: {}", e)
+            }))
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Evaluation failed: {}", e)
+            }))
+        }
+    };
+
+    let stored = serde_json::json!({
+        "source": source,
+        "metrics": report,
+    });
+    if let Err(e) = db.save_model_report(&version, kind, &stored).await {
+        eprintln!("Failed to save evaluation for {}: {}", version, e);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "version": version,
+        "source": source,
+        "metrics": report,
+    }))
+}
+
+// Re-tune a version's decision threshold on the data it was trained from
+// (the dataset, plus recorded outcomes for versions retrained on them) and
+// register the result as a new, inactive version; the original stays untouched
+async fn tune_model_threshold(
+    path: web::Path<String>,
+    objective: web::Json<ThresholdObjective>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = path.into_inner();
+    let model = match db.get_model_version(&version).await {
+        Ok(Some(record)) => match parse_artifact(&record) {
+            Ok(model) => model,
+            Err(e) => return registry_error_response(e),
+        },
+        Ok(None) => return registry_error_response(RegistryError::NotFound(version)),
+        Err(e) => return registry_error_response(e.into()),
+    };
+
+    // Only the outcomes the version was retrained on, not ones recorded since
+    let outcomes = match model.training().outcomes_until {
+        Some(until) if model.training().outcome_records > 0 => match db.get_labelled_predictions().await {
+            Ok(labelled) => {
+                let trained_on: Vec<_> = labelled.into_iter().filter(|(_, outcome)| outcome.recorded_at <= until).collect();
+                outcome_records(&trained_on, &model)
+            }
+            Err(e) => return registry_error_response(e.into()),
+        },
+        _ => Vec::new(),
+    };
+
+    let tuned = match load_dataset(&config.data_path).and_then(|mut records| {
+        records.extend(outcomes);
+        model.with_threshold(&records, objective.into_inner())
+    }) {
+        Ok(tuned) => tuned,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Threshold tuning failed: {}", e)
+            }))
+        }
+    };
+
+    let model_version = match registry.register(&db, &tuned).await {
+        Ok(model_version) => model_version,
+        Err(e) => return registry_error_response(e),
+    };
+
+    let choice = tuned.training().threshold.clone();
+    let stored = serde_json::json!({ "tuned_from": version, "threshold": choice });
+    if let Err(e) = db.save_model_report(&model_version.version, "threshold", &stored).await {
+        eprintln!("Failed to save threshold tuning for {}: {}", model_version.version, e);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Model version with tuned threshold saved",
+        "tuned_from": version,
+        "version": model_version,
+        "threshold": choice,
+    }))
+}
+
+// Fairness audit of a model version across the requested demographic or
+// cohort columns, on uploaded data or the same seeded hold-out split as
+// /evaluate. The stored artifact is what gets audited, never a refit
+async fn audit_fairness(
+    req: web::Json<FairnessRequest>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = req.data.version.clone().unwrap_or_else(|| registry.active_version());
+    let model = match db.get_model_version(&version).await {
+        Ok(Some(record)) => match parse_artifact(&record) {
+            Ok(model) => model,
+            Err(e) => return registry_error_response(e),
+        },
+        Ok(None) => return registry_error_response(RegistryError::NotFound(version)),
+        Err(e) => return registry_error_response(e.into()),
+    };
+
+    let outcome = match &req.data.csv {
+        Some(csv) => parse_dataset(csv).map(|records| ("uploaded_csv", records)),
+        None => load_dataset(&config.data_path).map(|records| {
+            let (_, test) = stratified_train_test_split(&records, req.data.test_fraction, req.data.seed);
+            ("holdout", test)
+        }),
+    }
+    .and_then(|(source, records)| Ok((source, fairness_audit(&model, &records, &req.attributes)?)));
+
+    let (source, report) = match outcome {
+        Ok(result) => result,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Fairness audit failed: {}", e)
+            }))
+        }
+    };
+    let stored = serde_json::json!({
+        "source": source,
+        "fairness": report,
+    });
+    if let Err(e) = db.save_model_report(&version, "fairness", &stored).await {
+        eprintln!("Failed to save fairness audit for {}: {}", version, e);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "version": version,
+        "source": source,
+        "fairness": report,
+    }))
+}
+
+async fn activate_model_version(
+    path: web::Path<String>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    match registry.activate(&db, &path.into_inner()).await {
+        Ok(version) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Model version activated",
+            "version": version
+        })),
+        Err(e) => registry_error_response(e),
+    }
+}
+
+async fn rollback_model_version(
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    match registry.rollback(&db).await {
+        Ok(version) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Rolled back to previous model version",
+            "version": version
+        })),
+        Err(e) => registry_error_response(e),
+    }
+}
+
+// Run `version` silently alongside the active model on every prediction
+async fn start_shadowing(
+    path: web::Path<String>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    match registry.set_challenger(&db, &path.into_inner()).await {
+        Ok(version) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Challenger is now shadowing the active version",
+            "active_version": registry.active_version(),
+            "challenger": version,
+        })),
+        Err(e) => registry_error_response(e),
+    }
+}
+
+async fn stop_shadowing(registry: web::Data<ModelRegistry>) -> HttpResponse {
+    match registry.clear_challenger() {
+        Some(version) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Stopped shadowing",
+            "challenger": version,
+        })),
+        None => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No challenger is shadowing the active version"
+        })),
+    }
+}
+
+// Agreement and (once outcomes are recorded) accuracy of a challenger against
+// the champion predictions it shadowed; defaults to the current challenger
+async fn get_shadow_report(
+    query: web::Query<ShadowReportQuery>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let Some(version) = query.version.clone().or_else(|| registry.challenger().map(|(version, _)| version)) else {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No challenger is shadowing the active version"
+        }));
+    };
+
+    match db.get_shadow_comparisons(&version).await {
+        Ok(comparisons) => HttpResponse::Ok().json(shadow_report(&version, &comparisons)),
+        Err(e) => registry_error_response(e.into()),
+    }
+}
+
+// Drift endpoint: incoming feature distributions against the training data
+async fn get_drift_report(
+    query: web::Query<DriftQuery>,
+    config: web::Data<AppConfig>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let reference = match load_dataset(&config.data_path) {
+        Ok(records) => records,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to load training data: {}", e)
+            }))
+        }
+    };
+
+    if !(MIN_DRIFT_SAMPLE..=MAX_DRIFT_WINDOW_SIZE).contains(&query.window_size) || !(1..=MAX_DRIFT_WINDOWS).contains(&query.windows) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!(
+                "window_size must be between {} and {}, and windows between 1 and {}",
+                MIN_DRIFT_SAMPLE, MAX_DRIFT_WINDOW_SIZE, MAX_DRIFT_WINDOWS
+            )
+        }));
+    }
+
+    let limit = query.window_size.saturating_mul(query.windows) as i64;
+    match db.get_recent_predictions(limit).await {
+        Ok(predictions) => {
+            let report = monitor_drift(&reference, &predictions, query.window_size);
+            if report.retraining_recommended {
+                eprintln!("⚠️ Feature drift detected in {:?}; retraining recommended", report.drifted_features);
+            }
+            HttpResponse::Ok().json(report)
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to load predictions: {}", e)
+        })),
+    }
+}
+
+// Record what actually happened to a student so their stored predictions can
+// be used as training data
+async fn record_outcome(
+    req: web::Json<OutcomeRequest>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    peers: web::Data<PeerDirectory>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Some(grade) = req.grade.as_deref().filter(|g| grade_rank(g).is_none()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown grade '{}'", grade)
+        }));
+    }
+    // Every /predict request is stored under the same placeholder name, so an
+    // outcome for it would label other students' predictions too
+    if req.name.trim().is_empty() || req.name == ANONYMOUS_STUDENT {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Outcomes need the name of a student with named predictions, e.g. from /batch-predict"
+        }));
+    }
+
+    let outcome = StudentOutcome {
+        name: req.name.clone(),
+        passed: req.passed,
+        grade: req.grade.clone(),
+        recorded_at: chrono::Utc::now(),
+    };
+
+    match db.save_outcome(&outcome).await {
+        Ok(0) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("No stored predictions for {}", outcome.name)
+        })),
+        Ok(labelled_predictions) => {
+            // The student can now show up in "students like you" lookups
+            let rebuilt = match db.get_labelled_predictions().await {
+                Ok(labelled) => load_dataset(&config.data_path)
+                    .map(|dataset| PeerIndex::build(&dataset, &outcome_records(&labelled, &registry.current()))),
+                Err(e) => Err(e.into()),
+            };
+            match rebuilt {
+                Ok(index) => peers.replace(index),
+                Err(e) => eprintln!("Failed to rebuild the similar students index: {}", e),
+            }
+
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Outcome recorded",
+                "outcome": outcome,
+                "labelled_predictions": labelled_predictions,
             }))
         }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to save outcome: {}", e)
+        })),
     }
 }
 
+// Retrain the active configuration on the dataset plus every recorded outcome
+// and register the candidate as a new, inactive version only when it beats
+// the active version on a holdout
+async fn retrain_from_outcomes(
+    options: web::Json<RetrainOptions>,
+    config: web::Data<AppConfig>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let labelled = match db.get_labelled_predictions().await {
+        Ok(labelled) => labelled,
+        Err(e) => return registry_error_response(e.into()),
+    };
+    if labelled.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "No outcomes have been recorded yet"
+        }));
+    }
+
+    let active_version = registry.active_version();
+    let active = registry.current();
+    let outcomes_until = labelled.iter().map(|(_, outcome)| outcome.recorded_at).max();
+    let outcome = load_dataset(&config.data_path).and_then(|base| {
+        retrain_candidate(&base, &outcome_records(&labelled, &active), outcomes_until, &active, &active.training().config, &options)
+    });
+
+    let (candidate, report) = match outcome {
+        Ok(result) => result,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Retraining failed: {}", e)
+            }))
+        }
+    };
+
+    let stored = serde_json::json!({ "compared_with": active_version, "retraining": report });
+    if !report.improved {
+        if let Err(e) = db.save_model_report(&active_version, "retraining", &stored).await {
+            eprintln!("Failed to save retraining report for {}: {}", active_version, e);
+        }
+        return HttpResponse::Ok().json(serde_json::json!({
+            "message": "Candidate did not beat the active version; nothing registered",
+            "active_version": active_version,
+            "version": null,
+            "retraining": report,
+        }));
+    }
+
+    let model_version = match registry.register(&db, &candidate).await {
+        Ok(model_version) => model_version,
+        Err(e) => return registry_error_response(e),
+    };
+    if let Err(e) = db.save_model_report(&model_version.version, "retraining", &stored).await {
+        eprintln!("Failed to save retraining report for {}: {}", model_version.version, e);
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Retrained model saved as a new model version",
+        "active_version": active_version,
+        "version": model_version,
+        "retraining": report,
+    }))
+}
+
+// Nudge a logistic model towards newly recorded outcomes without a full
+// retrain. Every step is checkpointed as a new model version
+async fn incremental_update(
+    req: web::Json<IncrementalUpdateRequest>,
+    registry: web::Data<ModelRegistry>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let version = req.version.clone().unwrap_or_else(|| registry.active_version());
+    let model = match db.get_model_version(&version).await {
+        Ok(Some(record)) => match parse_artifact(&record) {
+            Ok(model) => model,
+            Err(e) => return registry_error_response(e),
+        },
+        Ok(None) => return registry_error_response(RegistryError::NotFound(version)),
+        Err(e) => return registry_error_response(e.into()),
+    };
+
+    let learned_until = model.outcomes_learned_until();
+    let new_outcomes: Vec<_> = match db.get_labelled_predictions().await {
+        Ok(labelled) => labelled
+            .into_iter()
+            .filter(|(_, outcome)| learned_until.is_none_or(|t| outcome.recorded_at > t))
+            .collect(),
+        Err(e) => return registry_error_response(e.into()),
+    };
+    let last_outcome_at = new_outcomes.iter().map(|(_, outcome)| outcome.recorded_at).max();
+
+    let (updated, step) = match model.with_incremental_update(&version, &outcome_records(&new_outcomes, &model), last_outcome_at, &req.options) {
+        Ok(result) => result,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Incremental update failed: {}", e)
+            }))
+        }
+    };
+    if step.capped {
+        eprintln!("⚠️ Incremental update of {} hit the weight drift cap of {}", version, req.options.max_weight_drift);
+    }
+
+    let mut model_version = match registry.register(&db, &updated).await {
+        Ok(model_version) => model_version,
+        Err(e) => return registry_error_response(e),
+    };
+    let stored = serde_json::json!({ "options": req.options, "step": step });
+    if let Err(e) = db.save_model_report(&model_version.version, "incremental_update", &stored).await {
+        eprintln!("Failed to save incremental update for {}: {}", model_version.version, e);
+    }
+    if req.activate {
+        model_version = match registry.activate(&db, &model_version.version).await {
+            Ok(model_version) => model_version,
+            Err(e) => return registry_error_response(e),
+        };
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Incremental update saved as a new model version",
+        "version": model_version,
+        "step": step,
+    }))
+}
+
 // Model info endpoint
-async fn get_model_info(model_data: web::Data<ModelInfo>) -> HttpResponse {
-    HttpResponse::Ok().json(model_data.as_ref().clone())
+async fn get_model_info(registry: web::Data<ModelRegistry>) -> HttpResponse {
+    let mut model_info = registry.current().metadata();
+    model_info.version = Some(registry.active_version());
+    HttpResponse::Ok().json(model_info)
+}
+
+// Rule export for the active model, so non-technical staff can audit it
+async fn get_model_rules(registry: web::Data<ModelRegistry>) -> HttpResponse {
+    let model = registry.current();
+    match model.rules() {
+        Some(rules) => HttpResponse::Ok().json(serde_json::json!({
+            "version": registry.active_version(),
+            "model_type": model.metadata().model_type,
+            "rules": rules,
+        })),
+        None => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("{} models cannot be exported as rules", model.family().display_name())
+        })),
+    }
 }
 
 // Health check endpoint
@@ -1142,7 +2125,7 @@ David Lemoita,7.5,88.0" rows="8"></textarea>
                 resultDiv.className = 'result pass';
                 resultDiv.innerHTML = `
                     <h3>🎉 Prediction Result: PASS</h3>
-                    <p><strong>Confidence:</strong> ${(result.confidence * 100).toFixed(1)}%</p>
+                    <p><strong>Pass probability:</strong> ${(result.confidence * 100).toFixed(1)}%</p>
                     <p><strong>Study Hours:</strong> ${hours} hours/week</p>
                     <p><strong>Attendance:</strong> ${attendance}%</p>
                     <p>✅ Great job! Your current study habits and attendance should lead to success.</p>
@@ -1151,12 +2134,19 @@ David Lemoita,7.5,88.0" rows="8"></textarea>
                 resultDiv.className = 'result fail';
                 resultDiv.innerHTML = `
                     <h3>⚠️ Prediction Result: NEEDS IMPROVEMENT</h3>
-                    <p><strong>Confidence:</strong> ${(result.confidence * 100).toFixed(1)}%</p>
+                    <p><strong>Pass probability:</strong> ${(result.confidence * 100).toFixed(1)}%</p>
                     <p><strong>Study Hours:</strong> ${hours} hours/week</p>
                     <p><strong>Attendance:</strong> ${attendance}%</p>
                     <p>💡 Consider increasing study hours and improving attendance for better results.</p>
                 `;
             }
+
+            if (result.interval) {
+                resultDiv.innerHTML += `<p><strong>Likely range:</strong> ${(result.interval.lower * 100).toFixed(1)}% - ${(result.interval.upper * 100).toFixed(1)}%</p>`;
+            }
+            if (result.needs_review) {
+                resultDiv.innerHTML += `<p>🔍 This prediction is uncertain and has been flagged for review by an advisor.</p>`;
+            }
             
             resultDiv.style.display = 'block';
         }
@@ -1315,7 +2305,7 @@ David Lemoita,7.5,88.0" rows="8"></textarea>
                             <th>Study Hours</th>
                             <th>Attendance</th>
                             <th>Prediction</th>
-                            <th>Confidence</th>
+                            <th>Pass Probability</th>
                         </tr>
                     </thead>
                     <tbody>
@@ -1512,7 +2502,7 @@ David Lemoita,7.5,88.0" rows="8"></textarea>
                             <th>Study Hours</th>
                             <th>Attendance</th>
                             <th>Prediction</th>
-                            <th>Confidence</th>
+                            <th>Pass Probability</th>
                             <th>Date</th>
                         </tr>
                     </thead>
@@ -1898,21 +2888,30 @@ David Lemoita,7.5,88.0" rows="8"></textarea>
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // Initialize the ML model
-    let model_info = train_model();
-    let trained_model = TrainedModel::new();
+    // Load the model artifact, or train one from the student dataset
+    let config = AppConfig::from_env();
+    let trained_model = load_or_train_model(&config.data_path, config.model_path.as_deref(), &config.training_config())
+        .expect("Failed to load or train model");
     
     // Initialize database
     let database = Database::new().await
         .expect("Failed to initialize database");
+
+    // Serve the active registered version, registering the startup model if there is none
+    let registry = ModelRegistry::bootstrap(&database, trained_model).await
+        .expect("Failed to initialize model registry");
     
+    // Index past students for "students like you" lookups
+    let peer_index = PeerIndex::build(&load_dataset(&config.data_path).expect("Failed to load student dataset"), &[]);
+
     // Initialize gamification engine
     let gamification_engine = GamificationEngine::new();
     
     // Create application data
-    let model_data = web::Data::new(model_info);
-    let trained_model_data = web::Data::new(trained_model);
+    let config_data = web::Data::new(config);
+    let registry_data = web::Data::new(registry);
     let db_data = web::Data::new(database);
+    let peers_data = web::Data::new(PeerDirectory::new(peer_index));
     let gamification_data = web::Data::new(gamification_engine);
     
     println!("🚀 Starting TUK Student Classifier Server at http://localhost:8080");
@@ -1922,24 +2921,45 @@ async fn main() -> std::io::Result<()> {
     
     HttpServer::new(move || {
         App::new()
-            .app_data(model_data.clone())
-            .app_data(trained_model_data.clone())
+            .app_data(config_data.clone())
+            .app_data(registry_data.clone())
             .app_data(db_data.clone())
+            .app_data(peers_data.clone())
             .app_data(gamification_data.clone())
             .route("/", web::get().to(serve_homepage))
             .route("/predict", web::post().to(predict))
             .route("/batch-predict", web::post().to(batch_predict))
+            .route("/counterfactual", web::post().to(counterfactual))
+            .route("/similar-students", web::post().to(similar_students))
             .route("/analytics", web::get().to(get_analytics))
             .route("/database-analytics", web::get().to(get_database_analytics))
             .route("/success-tips", web::get().to(get_success_tips))
             .route("/model-info", web::get().to(get_model_info))
+            .route("/model-rules", web::get().to(get_model_rules))
             .route("/health", web::get().to(health_check))
             .route("/student-trends", web::post().to(get_student_trends))
             .route("/class-trends", web::get().to(get_class_trends))
+            .route("/weekly-history", web::post().to(record_weekly_history))
             .route("/trends-dashboard", web::get().to(get_trends_dashboard))
             .route("/real-trends-dashboard", web::get().to(get_real_trends_dashboard))
             .route("/all-predictions", web::get().to(get_all_predictions))
+            .route("/drift", web::get().to(get_drift_report))
+            .route("/outcomes", web::post().to(record_outcome))
+            .route("/retrain", web::post().to(retrain_from_outcomes))
+            .route("/incremental-update", web::post().to(incremental_update))
             .route("/save-model-version", web::post().to(save_model_version))
+            .route("/models", web::get().to(list_model_versions))
+            .route("/models/rollback", web::post().to(rollback_model_version))
+            .route("/models/shadow", web::delete().to(stop_shadowing))
+            .route("/models/{version}", web::get().to(get_model_version))
+            .route("/models/{version}/activate", web::post().to(activate_model_version))
+            .route("/models/{version}/threshold", web::post().to(tune_model_threshold))
+            .route("/models/{version}/shadow", web::post().to(start_shadowing))
+            .route("/shadow-report", web::get().to(get_shadow_report))
+            .route("/evaluate", web::post().to(evaluate))
+            .route("/fairness", web::post().to(audit_fairness))
+            .route("/cross-validate", web::post().to(cross_validate_model))
+            .route("/hyperparameter-search", web::post().to(search_hyperparameters))
             .route("/track-progress", web::post().to(track_student_progress))
             .route("/generate-study-plan", web::post().to(generate_study_plan))
             // NEW: Gamification endpoints
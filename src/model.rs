@@ -1,17 +1,165 @@
 use serde::{Deserialize, Serialize};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bootstrap::{BootstrapEnsemble, PredictionInterval, BOOTSTRAP_SEED};
+use crate::calibration::{CalibrationMethod, Calibrator};
+use crate::data::{load_dataset, Result, TrainingRecord};
+use crate::counterfactual::{find_counterfactual, find_counterfactual_by, FeatureBounds};
+use crate::incremental::{cap_drift, IncrementalOptions, IncrementalState, IncrementalStep};
+use crate::peers::PeerEvidence;
+use crate::preprocessing::{PreprocessingConfig, Preprocessor};
+use crate::grades::{grade_for, grade_rank, probability_at_least, with_pass_probability, GradeModel, GradeProbability, GRADE_BANDS};
+use crate::evaluation::{cross_validate_with_predictions, expected_calibration_error, DEFAULT_CALIBRATION_BINS, DEFAULT_CV_FOLDS, DEFAULT_CV_SEED};
+use crate::threshold::{tune_threshold, ThresholdChoice, ThresholdObjective, DEFAULT_DECISION_THRESHOLD};
+use crate::predictor::{imputed_flags, training_accuracy, Estimator, ModelFamily, Predictor, Standardizer};
+
+// Fallbacks for optional features, taken from the medians of data/students.csv
+pub const DEFAULT_PREVIOUS_GRADES: f64 = 70.0;
+pub const DEFAULT_SLEEP_HOURS: f64 = 7.0;
+
+// Supplied features further than this many training standard deviations
+// from the training mean mark a student as out of distribution
+pub const OUT_OF_DISTRIBUTION_Z: f64 = 3.0;
+
+// Model inputs for a single student. `previous_grades` (0-100) and
+// `sleep_hours` (per night) are optional; when absent the documented
+// defaults above are used
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StudentFeatures {
+    pub hours: f64,
+    pub attendance: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_grades: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_hours: Option<f64>,
+}
+
+impl StudentFeatures {
+    pub fn new(hours: f64, attendance: f64) -> Self {
+        Self {
+            hours,
+            attendance,
+            previous_grades: None,
+            sleep_hours: None,
+        }
+    }
+
+    // Values in `FEATURE_NAMES` order with defaults filled in
+    pub fn to_vector(&self) -> Vec<f64> {
+        vec![
+            self.hours,
+            self.attendance,
+            self.previous_grades.unwrap_or(DEFAULT_PREVIOUS_GRADES),
+            self.sleep_hours.unwrap_or(DEFAULT_SLEEP_HOURS),
+        ]
+    }
+
+    // Inverse of `to_vector`; every feature counts as supplied
+    pub fn from_vector(vector: &[f64]) -> Self {
+        Self {
+            hours: vector[0],
+            attendance: vector[1],
+            previous_grades: Some(vector[2]),
+            sleep_hours: Some(vector[3]),
+        }
+    }
+}
+
+impl From<&TrainingRecord> for StudentFeatures {
+    fn from(record: &TrainingRecord) -> Self {
+        Self {
+            hours: record.hours,
+            attendance: record.attendance,
+            previous_grades: Some(record.previous_grades),
+            sleep_hours: Some(record.sleep_hours),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StudentRecord {
     pub name: String,
-    pub hours: f64,
-    pub attendance: f64,
+    #[serde(flatten)]
+    pub features: StudentFeatures,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PredictResponse {
     pub prediction: String,
-    pub confidence: f64,
+    pub confidence: f64, // Calibrated P(pass)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uncertainty: Option<UncertaintyEstimate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<PredictionExplanation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_grade: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade_distribution: Option<Vec<GradeProbability>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar_students: Option<PeerEvidence>, // Past students with similar habits and their results
+    #[serde(flatten)]
+    pub review: PredictionReview,
+}
+
+// Whether a prediction is reliable enough to act on without an advisor
+// looking at it first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PredictionReview {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<PredictionInterval>, // Bootstrap range of P(pass)
+    pub out_of_distribution: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unusual_features: Vec<String>, // Supplied features far outside the training data
+    pub needs_review: bool, // Out of distribution, or the interval spans the pass cutoff
+}
+
+// How far one feature moved the student's log-odds of passing away from
+// those of an average student in the training data
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureContribution {
+    pub feature: String,
+    pub value: f64,
+    pub contribution: f64,
+    pub imputed: bool, // The value is a documented default, not a supplied one
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PredictionExplanation {
+    pub baseline_log_odds: f64, // Log-odds for an average training student
+    pub contributions: Vec<FeatureContribution>, // Largest effect first
+}
+
+// Opt-in Monte Carlo uncertainty; the seed makes the estimate reproducible
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UncertaintyOptions {
+    pub seed: u64,
+    #[serde(default = "default_uncertainty_samples")]
+    pub samples: usize,
+}
+
+fn default_uncertainty_samples() -> usize {
+    200
+}
+
+pub const MAX_UNCERTAINTY_SAMPLES: usize = 10_000;
+
+impl UncertaintyOptions {
+    // Keeps one request from drawing an unbounded number of samples
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_UNCERTAINTY_SAMPLES).contains(&self.samples) {
+            return Err(format!("samples must be between 1 and {}", MAX_UNCERTAINTY_SAMPLES).into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UncertaintyEstimate {
+    pub mean_probability: f64,
+    pub std_dev: f64,
+    pub samples: usize,
+    pub seed: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,19 +172,26 @@ pub struct BatchPredictResponse {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StudentPrediction {
     pub name: String,
-    pub hours: f64,
-    pub attendance: f64,
+    #[serde(flatten)]
+    pub features: StudentFeatures,
     pub prediction: String,
-    pub confidence: f64,
+    pub confidence: f64, // Calibrated P(pass)
     pub recommendation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<PredictionExplanation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_grade: Option<String>,
+    #[serde(flatten)]
+    pub review: PredictionReview,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchSummary {
     pub pass_count: usize,
     pub fail_count: usize,
+    pub review_count: usize, // Students flagged for manual review
     pub pass_rate: f64,
-    pub avg_confidence: f64,
+    pub avg_confidence: f64, // Mean P(pass) across the batch
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,6 +201,19 @@ pub struct ModelInfo {
     pub training_data_size: usize,
     pub model_type: String,
     pub last_updated: String,
+    pub accuracy_method: String, // How `accuracy` was measured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grade_bands: Vec<String>, // Empty when no grade model was trained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy_std: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<String>, // How raw scores are mapped to P(pass)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration_error: Option<f64>, // Expected calibration error on held-out predictions
+    #[serde(default = "default_decision_threshold")]
+    pub decision_threshold: f64, // P(pass) at or above which a student is predicted to pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>, // Registry version, when served from the registry
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -73,6 +241,8 @@ pub struct StudyPlanRequest {
     pub target_grade: String, // "A", "B", "C", "Pass"
     pub available_days: Vec<String>, // ["Monday", "Tuesday", ...]
     pub preferred_times: Vec<String>, // ["Morning", "Afternoon", "Evening"]
+    #[serde(default)]
+    pub seed: Option<u64>, // Shuffle study blocks reproducibly; omit for a fixed rotation
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -85,6 +255,10 @@ pub struct StudyPlan {
     pub weekly_schedule: Vec<DailySchedule>,
     pub recommendations: Vec<String>,
     pub expected_outcomes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_grade: Option<String>, // At the student's current hours and attendance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_grade_probability: Option<f64>, // Current chance of the target grade or better
     pub generated_at: String,
 }
 
@@ -102,69 +276,484 @@ pub struct StudyBlock {
     pub duration: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingConfig {
+    #[serde(default)]
+    pub family: ModelFamily,
+    pub learning_rate: f64,
+    pub epochs: usize,
+    pub l2_penalty: f64,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize, // Tree families only
+    #[serde(default = "default_min_samples_leaf")]
+    pub min_samples_leaf: usize, // Tree families only
+    #[serde(default = "default_n_estimators")]
+    pub n_estimators: usize, // Gradient boosting only; also uses `learning_rate`
+    #[serde(default = "default_k_neighbours")]
+    pub k_neighbours: usize, // KNN only
+    #[serde(default)]
+    pub calibration: Option<CalibrationMethod>, // None serves the family's raw scores
+    #[serde(default = "default_bootstrap_models")]
+    pub bootstrap_models: usize, // Ensemble size for prediction intervals; 0 disables them
+    #[serde(default)]
+    pub threshold: ThresholdObjective, // Tuned on the calibrated out-of-fold predictions
+    #[serde(default)]
+    pub preprocessing: PreprocessingConfig,
+}
+
+fn default_decision_threshold() -> f64 {
+    DEFAULT_DECISION_THRESHOLD
+}
+
+fn default_bootstrap_models() -> usize {
+    20
+}
+
+fn default_max_depth() -> usize {
+    4
+}
+
+fn default_min_samples_leaf() -> usize {
+    1
+}
+
+fn default_n_estimators() -> usize {
+    50
+}
+
+fn default_k_neighbours() -> usize {
+    3
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            family: ModelFamily::default(),
+            learning_rate: 0.1,
+            epochs: 2000,
+            l2_penalty: 0.01,
+            max_depth: default_max_depth(),
+            min_samples_leaf: default_min_samples_leaf(),
+            n_estimators: default_n_estimators(),
+            k_neighbours: default_k_neighbours(),
+            calibration: Some(CalibrationMethod::Platt),
+            bootstrap_models: default_bootstrap_models(),
+            threshold: ThresholdObjective::default(),
+            preprocessing: PreprocessingConfig::default(),
+        }
+    }
+}
+
+// Features the model is trained on, in the order `predict` expects them
+pub const FEATURE_NAMES: [&str; 4] = ["study_hours", "attendance", "previous_grades", "sleep_hours"];
+
+// Bump whenever the serialized layout of `TrainedModel` changes
+pub const MODEL_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingMetadata {
+    pub training_size: usize,
+    pub training_accuracy: f64,
+    pub trained_at: String,
+    pub config: TrainingConfig,
+    #[serde(default)]
+    pub validation: Option<ValidationSummary>,
+    #[serde(default)]
+    pub threshold: Option<ThresholdChoice>, // None means the default cutoff
+    #[serde(default)]
+    pub incremental: Option<IncrementalState>, // Set once SGD steps have been applied
+    #[serde(default)]
+    pub outcome_records: usize, // Recorded outcomes merged into the training data
+    #[serde(default)]
+    pub outcomes_until: Option<chrono::DateTime<chrono::Utc>>, // Newest of those outcomes
+}
+
+// Out-of-sample accuracy from cross-validating the training configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    pub folds: usize,
+    pub accuracy_mean: f64,
+    pub accuracy_std: f64,
+    #[serde(default)]
+    pub calibration_error: Option<f64>, // Of the calibrated out-of-fold predictions
+}
+
+// The servable model: a pass/fail estimator of the configured family plus
+// the optional grade-band head. Serializes to the model artifact shared by
+// the server and offline tooling
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainedModel {
-    study_hours_threshold: f64,
-    attendance_threshold: f64,
-    base_accuracy: f64,
+    format_version: u32,
+    feature_names: Vec<String>,
+    estimator: Estimator,
+    standardizer: Standardizer, // Training feature statistics
+    metadata: TrainingMetadata,
+    #[serde(default)]
+    grade_model: Option<GradeModel>, // Trained when the data has grade labels
+    #[serde(default)]
+    calibrator: Option<Calibrator>, // Fitted on out-of-fold predictions
+    #[serde(default)]
+    bootstrap: Option<BootstrapEnsemble>, // For prediction intervals
+    #[serde(default)]
+    preprocessor: Preprocessor, // Applied to every input before the estimator
 }
 
 impl TrainedModel {
-    pub fn new() -> Self {
-        Self {
-            study_hours_threshold: 5.0,
-            attendance_threshold: 75.0,
-            base_accuracy: 0.85,
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        if records.is_empty() {
+            return Err("Cannot train a model without records".into());
+        }
+
+        let rows: Vec<Vec<f64>> = records.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+        let standardizer = Standardizer::fit(&rows);
+        let preprocessor = Preprocessor::fit(&rows, &config.preprocessing);
+        let estimator = Estimator::fit(records, config)?;
+
+        let mut model = Self {
+            format_version: MODEL_FORMAT_VERSION,
+            feature_names: FEATURE_NAMES.iter().map(|f| f.to_string()).collect(),
+            estimator,
+            standardizer,
+            metadata: TrainingMetadata {
+                training_size: records.len(),
+                training_accuracy: 0.0,
+                trained_at: chrono::Utc::now().to_rfc3339(),
+                config: config.clone(),
+                validation: None,
+                threshold: None,
+                incremental: None,
+                outcome_records: 0,
+                outcomes_until: None,
+            },
+            grade_model: None,
+            calibrator: None,
+            bootstrap: None,
+            preprocessor,
+        };
+
+        // Grade bands are only learned when at least two distinct bands are labelled
+        let graded: Vec<(Vec<f64>, usize)> = rows
+            .iter()
+            .zip(records)
+            .filter_map(|(x, r)| r.grade.as_deref().and_then(grade_rank).map(|g| (model.standardizer.transform(x), g)))
+            .collect();
+        let mut distinct: Vec<usize> = graded.iter().map(|(_, g)| *g).collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() >= 2 {
+            let (rows, labels): (Vec<Vec<f64>>, Vec<usize>) = graded.into_iter().unzip();
+            model.grade_model = Some(GradeModel::fit(&rows, &labels, config));
         }
+
+        model.metadata.training_accuracy = training_accuracy(&model, records);
+
+        Ok(model)
     }
 
-    pub fn predict(&self, features: &[f64]) -> (bool, f64) {
-        let hours = features[0];
-        let attendance = features[1];
-        
-        let mut score = 0.0;
-        
-        if hours >= 8.0 {
-            score += 0.6;
-        } else if hours >= 6.0 {
-            score += 0.5;
-        } else if hours >= 4.0 {
-            score += 0.3;
-        } else {
-            score += 0.1;
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    // Parses an artifact, rejecting ones this build cannot score
+    pub fn from_json(json: &str) -> Result<Self> {
+        let model: Self = serde_json::from_str(json)?;
+
+        if model.format_version != MODEL_FORMAT_VERSION {
+            return Err(format!(
+                "Model artifact has format version {}, expected {}",
+                model.format_version, MODEL_FORMAT_VERSION
+            ).into());
         }
-        
-        if attendance >= 90.0 {
-            score += 0.4;
-        } else if attendance >= 80.0 {
-            score += 0.35;
-        } else if attendance >= 70.0 {
-            score += 0.25;
-        } else {
-            score += 0.1;
+        if model.feature_names != FEATURE_NAMES {
+            return Err(format!(
+                "Model artifact was trained on features {:?}, expected {:?}",
+                model.feature_names, FEATURE_NAMES
+            ).into());
         }
-        
-        let mut rng = rand::thread_rng();
-        let noise: f64 = rng.gen_range(-0.1..0.1);
-        let final_score = (score + noise).clamp(0.0, 1.0);
-        
-        let prediction = final_score >= 0.5;
-        let confidence = if prediction { final_score } else { 1.0 - final_score };
-        
-        (prediction, confidence)
+        let dims = model.feature_names.len();
+        let estimator_dims = match &model.estimator {
+            Estimator::LogisticRegression(lr) => lr.dimensions(),
+            _ => dims,
+        };
+        if estimator_dims != dims || model.standardizer.means.len() != dims || model.standardizer.stds.len() != dims {
+            return Err("Model artifact has mismatched parameter lengths".into());
+        }
+
+        Ok(model)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
     }
 
-    pub fn batch_predict(&self, students: Vec<StudentRecord>) -> BatchPredictResponse {
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    // A copy of this model with its cutoff re-tuned for `objective` on
+    // `records`, using the same calibrated out-of-fold predictions training
+    // would. `records` must be the data the model was trained from, with the
+    // recorded outcomes up to `outcomes_until` when `outcome_records` is set,
+    // or the calibrator and cutoff would describe different data.
+    // Incrementally updated models are rejected since refitting their config
+    // cannot reproduce the SGD steps. Everything else about the model is
+    // unchanged
+    pub fn with_threshold(&self, records: &[TrainingRecord], objective: ThresholdObjective) -> Result<Self> {
+        objective.validate()?;
+        if let Some(state) = &self.metadata.incremental {
+            return Err(format!(
+                "Incrementally updated models cannot be re-tuned; re-tune {} and update it again",
+                state.base_version
+            ).into());
+        }
+
+        let config = &self.metadata.config;
+        let (_, out_of_fold) = cross_validate_with_predictions(records, config, DEFAULT_CV_FOLDS, DEFAULT_CV_SEED)?;
+        let (scores, labels): (Vec<f64>, Vec<bool>) = out_of_fold.into_iter().unzip();
+        let calibrated: Vec<f64> = scores.iter().map(|&s| self.calibrate(s)).collect();
+
+        let mut model = self.clone();
+        model.metadata.config.threshold = objective;
+        model.metadata.threshold = Some(tune_threshold(&calibrated, &labels, objective));
+        model.metadata.training_accuracy = training_accuracy(&model, records);
+
+        Ok(model)
+    }
+
+    // A copy of this (logistic) model after SGD on newly labelled `records`.
+    // `version` is this model's registered version. Weights may move at most
+    // `max_weight_drift` from the fully trained version the chain of steps
+    // started from. The calibrator and bootstrap ensemble are dropped: both
+    // were fitted to the old weights and would misdescribe the new ones. The
+    // cutoff moves to the raw score the calibrator mapped onto it, so it
+    // splits the students it was chosen on the same way
+    pub fn with_incremental_update(
+        &self,
+        version: &str,
+        records: &[TrainingRecord],
+        last_outcome_at: Option<chrono::DateTime<chrono::Utc>>,
+        options: &IncrementalOptions,
+    ) -> Result<(Self, IncrementalStep)> {
+        options.validate()?;
+        if records.is_empty() {
+            return Err("No new labelled records to learn from".into());
+        }
+
+        // Clipped the way serving will see them
+        let prepared: Vec<TrainingRecord> = records
+            .iter()
+            .map(|r| {
+                let vector = self.prepare(&StudentFeatures::from(r)).to_vector();
+                TrainingRecord {
+                    hours: vector[0],
+                    attendance: vector[1],
+                    previous_grades: vector[2],
+                    sleep_hours: vector[3],
+                    ..r.clone()
+                }
+            })
+            .collect();
+
+        let mut model = self.clone();
+        let Estimator::LogisticRegression(lr) = &mut model.estimator else {
+            return Err("Incremental updates need a logistic regression model".into());
+        };
+
+        let (base_version, base_parameters, steps, records_seen) = match &self.metadata.incremental {
+            Some(state) => (state.base_version.clone(), state.base_parameters.clone(), state.steps, state.records_seen),
+            None => (version.to_string(), lr.parameters(), 0, 0),
+        };
+
+        lr.sgd_update(&prepared, options.learning_rate, self.metadata.config.l2_penalty, options.epochs);
+        let (parameters, weight_drift, capped) = cap_drift(&base_parameters, &lr.parameters(), options.max_weight_drift);
+        lr.set_parameters(&parameters);
+
+        model.bootstrap = None;
+        if let Some(calibrator) = model.calibrator.take() {
+            if let Some(choice) = &mut model.metadata.threshold {
+                choice.threshold = calibrator.inverse(choice.threshold);
+            }
+        }
+        model.metadata.training_size += records.len();
+        model.metadata.outcome_records += records.len();
+        model.metadata.trained_at = chrono::Utc::now().to_rfc3339();
+        model.metadata.incremental = Some(IncrementalState {
+            base_version: base_version.clone(),
+            base_parameters,
+            steps: steps + 1,
+            records_seen: records_seen + records.len(),
+            weight_drift,
+            last_outcome_at: last_outcome_at.or_else(|| self.metadata.incremental.as_ref().and_then(|s| s.last_outcome_at)),
+        });
+
+        let step = IncrementalStep {
+            updated_from: version.to_string(),
+            base_version,
+            step: steps + 1,
+            records: records.len(),
+            weight_drift,
+            capped,
+            batch_accuracy_before: training_accuracy(self, records),
+            batch_accuracy_after: training_accuracy(&model, records),
+        };
+
+        Ok((model, step))
+    }
+
+    // Newest recorded outcome this model has learned from, by incremental
+    // steps or, failing that, by being retrained on it
+    pub fn outcomes_learned_until(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.metadata
+            .incremental
+            .as_ref()
+            .and_then(|state| state.last_outcome_at)
+            .or(self.metadata.outcomes_until)
+    }
+
+    pub(crate) fn set_outcomes(&mut self, count: usize, until: Option<chrono::DateTime<chrono::Utc>>) {
+        self.metadata.outcome_records = count;
+        self.metadata.outcomes_until = until;
+    }
+
+    // Training standard deviation of a feature, in its original units
+    pub fn feature_std(&self, feature: &str) -> Option<f64> {
+        self.feature_names
+            .iter()
+            .position(|f| f == feature)
+            .map(|j| self.standardizer.stds[j])
+    }
+
+    pub fn training(&self) -> &TrainingMetadata {
+        &self.metadata
+    }
+
+    pub fn family(&self) -> ModelFamily {
+        self.estimator.family()
+    }
+
+    pub fn estimator(&self) -> &Estimator {
+        &self.estimator
+    }
+
+    // If/then rules for auditing tree-based models; None for other families
+    pub fn rules(&self) -> Option<Vec<String>> {
+        self.estimator.rules()
+    }
+
+    // The input the estimator sees: imputed and, when configured, clipped
+    pub fn prepare(&self, features: &StudentFeatures) -> StudentFeatures {
+        self.preprocessor.apply(features)
+    }
+
+    // Missing optional features filled in as the fitted pipeline would, but
+    // not clipped; for turning stored predictions into training data
+    pub fn impute(&self, features: &StudentFeatures) -> StudentFeatures {
+        self.preprocessor.impute(features)
+    }
+
+    fn calibrate(&self, score: f64) -> f64 {
+        match &self.calibrator {
+            Some(calibrator) => calibrator.apply(score),
+            None => score,
+        }
+    }
+
+    // Supplied features more than `OUT_OF_DISTRIBUTION_Z` training standard
+    // deviations from the mean. Imputed defaults are never unusual
+    pub fn unusual_features(&self, features: &StudentFeatures) -> Vec<String> {
+        let scaled = self.standardizer.transform(&features.to_vector());
+        let imputed = imputed_flags(features);
+
+        scaled
+            .iter()
+            .enumerate()
+            .filter(|&(j, z)| !imputed[j] && z.abs() > OUT_OF_DISTRIBUTION_Z)
+            .map(|(j, _)| self.feature_names[j].clone())
+            .collect()
+    }
+
+    // Bootstrap interval and out-of-distribution check. Borderline cases,
+    // whose interval reaches both sides of the pass cutoff, need review too
+    pub fn review(&self, features: &StudentFeatures) -> PredictionReview {
+        let interval = self
+            .bootstrap
+            .as_ref()
+            .and_then(|ensemble| ensemble.interval(&self.prepare(features), |score| self.calibrate(score)));
+        let unusual_features = self.unusual_features(features);
+        let out_of_distribution = !unusual_features.is_empty();
+        let threshold = self.decision_threshold();
+        let borderline = interval.as_ref().is_some_and(|i| i.lower < threshold && i.upper >= threshold);
+
+        PredictionReview {
+            interval,
+            out_of_distribution,
+            unusual_features,
+            needs_review: out_of_distribution || borderline,
+        }
+    }
+
+    // Probability of each grade band, when the model was trained with grades.
+    // The grade head only splits P(pass) between the passing bands, so
+    // P(grade >= Pass) always equals `predict_proba`
+    pub fn grade_distribution(&self, features: &StudentFeatures) -> Option<Vec<GradeProbability>> {
+        let grades = self.grade_model.as_ref()?;
+        let distribution = grades.distribution(&self.standardizer.transform(&self.prepare(features).to_vector()));
+        Some(with_pass_probability(&distribution, self.predict_proba(features)))
+    }
+
+    // "Fail" exactly when `predict` says the student fails
+    pub fn predicted_grade(&self, features: &StudentFeatures) -> Option<String> {
+        self.grade_distribution(features).map(|d| grade_for(&d, self.predict(features).0))
+    }
+
+    // Re-scores the student with each feature jittered by up to 10% of its
+    // training standard deviation, to show how sensitive the prediction is
+    // to small measurement errors in the inputs. At most
+    // `MAX_UNCERTAINTY_SAMPLES` samples are drawn
+    pub fn predict_with_uncertainty(&self, features: &StudentFeatures, options: &UncertaintyOptions) -> UncertaintyEstimate {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let samples = options.samples.clamp(1, MAX_UNCERTAINTY_SAMPLES);
+        let vector = features.to_vector();
+
+        let probabilities: Vec<f64> = (0..samples)
+            .map(|_| {
+                let jittered: Vec<f64> = vector
+                    .iter()
+                    .zip(&self.standardizer.stds)
+                    .map(|(x, std)| x + rng.gen_range(-0.1..0.1) * std)
+                    .collect();
+                self.predict_proba(&StudentFeatures::from_vector(&jittered))
+            })
+            .collect();
+
+        let mean_probability = probabilities.iter().sum::<f64>() / samples as f64;
+        let variance = probabilities.iter().map(|p| (p - mean_probability).powi(2)).sum::<f64>() / samples as f64;
+
+        UncertaintyEstimate {
+            mean_probability,
+            std_dev: variance.sqrt(),
+            samples,
+            seed: options.seed,
+        }
+    }
+
+    pub fn batch_predict(&self, students: Vec<StudentRecord>, include_explanations: bool) -> BatchPredictResponse {
         let mut predictions = Vec::new();
         let mut pass_count = 0;
         let mut fail_count = 0;
-        let mut total_confidence = 0.0;
+        let mut review_count = 0;
+        let mut total_probability = 0.0;
 
         for student in students {
-            let features = vec![student.hours, student.attendance];
-            let (prediction, confidence) = self.predict(&features);
-            
-            total_confidence += confidence;
+            let (prediction, _) = self.predict(&student.features);
+            let probability = self.predict_proba(&student.features);
+
+            total_probability += probability;
 
             if prediction {
                 pass_count += 1;
@@ -172,29 +761,23 @@ impl TrainedModel {
                 fail_count += 1;
             }
 
-            let recommendation = if prediction {
-                if confidence > 0.8 {
-                    "Continue current study habits".to_string()
-                } else {
-                    "Consider slight improvements".to_string()
-                }
-            } else {
-                if student.hours < 5.0 {
-                    "Increase study hours significantly".to_string()
-                } else if student.attendance < 70.0 {
-                    "Improve class attendance".to_string()
-                } else {
-                    "Seek academic support".to_string()
-                }
-            };
+            let explanation = self.explain(&student.features);
+            let recommendation = recommend(prediction, probability, &explanation);
+            let predicted_grade = self.predicted_grade(&student.features);
+            let review = self.review(&student.features);
+            if review.needs_review {
+                review_count += 1;
+            }
 
             predictions.push(StudentPrediction {
                 name: student.name,
-                hours: student.hours,
-                attendance: student.attendance,
+                features: student.features,
                 prediction: if prediction { "Pass".to_string() } else { "Fail".to_string() },
-                confidence,
+                confidence: probability,
                 recommendation,
+                explanation: if include_explanations { Some(explanation) } else { None },
+                predicted_grade,
+                review,
             });
         }
 
@@ -205,7 +788,7 @@ impl TrainedModel {
             0.0
         };
         let avg_confidence = if total_students > 0 {
-            total_confidence / total_students as f64
+            total_probability / total_students as f64
         } else {
             0.0
         };
@@ -215,6 +798,7 @@ impl TrainedModel {
             summary: BatchSummary {
                 pass_count,
                 fail_count,
+                review_count,
                 pass_rate,
                 avg_confidence,
             },
@@ -223,7 +807,7 @@ impl TrainedModel {
     }
 
     pub fn get_accuracy(&self) -> f64 {
-        self.base_accuracy
+        self.metadata().accuracy
     }
 
     // Study Plan Generation
@@ -237,7 +821,8 @@ impl TrainedModel {
         let weekly_schedule = self.generate_weekly_schedule(
             request.available_days.clone(),
             request.preferred_times.clone(),
-            target_hours
+            target_hours,
+            request.seed
         );
 
         let recommendations = self.generate_recommendations(
@@ -262,6 +847,9 @@ impl TrainedModel {
             target_attendance
         );
 
+        let current_features = StudentFeatures::new(request.current_hours, request.current_attendance);
+        let current_distribution = self.grade_distribution(&current_features);
+
         StudyPlan {
             student_name: request.student_name.clone(),
             target_grade: request.target_grade.clone(),
@@ -271,41 +859,53 @@ impl TrainedModel {
             weekly_schedule,
             recommendations,
             expected_outcomes,
+            predicted_grade: self.predicted_grade(&current_features),
+            target_grade_probability: current_distribution
+                .as_deref()
+                .and_then(|d| probability_at_least(d, &request.target_grade)),
             generated_at: chrono::Utc::now().to_rfc3339(),
         }
     }
 
+    // Smallest change after which the target grade (or better) becomes the
+    // likelier outcome. Without a grade model, falls back to the pass
+    // probability each grade calls for
     fn calculate_targets(&self, current_hours: f64, current_attendance: f64, target_grade: &str) -> (f64, f64) {
-        match target_grade {
-            "A" => (
-                current_hours.max(12.0),
-                current_attendance.max(95.0)
-            ),
-            "B" => (
-                current_hours.max(9.0),
-                current_attendance.max(85.0)
-            ),
-            "C" => (
-                current_hours.max(6.0),
-                current_attendance.max(75.0)
-            ),
-            "Pass" => (
-                current_hours.max(5.0),
-                current_attendance.max(70.0)
-            ),
-            _ => (
-                current_hours.max(8.0),
-                current_attendance.max(80.0)
-            )
-        }
+        let features = StudentFeatures::new(current_hours, current_attendance);
+        let bounds = FeatureBounds::default();
+
+        let counterfactual = match (&self.grade_model, grade_rank(target_grade)) {
+            // P(≥ Pass) is P(pass), so "Pass" is reached at the model's own cutoff
+            (Some(_), Some(_)) => {
+                let target_probability = if target_grade.eq_ignore_ascii_case("Pass") { self.decision_threshold() } else { 0.5 };
+                find_counterfactual_by(self, &features, target_probability, &bounds, |candidate| {
+                    self.grade_distribution(candidate)
+                        .and_then(|d| probability_at_least(&d, target_grade))
+                        .unwrap_or(0.0)
+                })
+            }
+            _ => {
+                let target_probability = match target_grade {
+                    "A" => 0.95,
+                    "B" => 0.85,
+                    "C" => 0.7,
+                    "Pass" => self.decision_threshold(),
+                    _ => 0.8,
+                };
+                find_counterfactual(self, &features, target_probability, &bounds)
+            }
+        };
+
+        (counterfactual.required_hours, counterfactual.required_attendance)
     }
 
-    fn generate_weekly_schedule(&self, available_days: Vec<String>, preferred_times: Vec<String>, total_hours: f64) -> Vec<DailySchedule> {
+    fn generate_weekly_schedule(&self, available_days: Vec<String>, preferred_times: Vec<String>, total_hours: f64, seed: Option<u64>) -> Vec<DailySchedule> {
         let mut schedule = Vec::new();
         let hours_per_day = total_hours / available_days.len() as f64;
+        let mut rng = seed.map(StdRng::seed_from_u64);
 
-        for day in available_days {
-            let study_blocks = self.generate_daily_blocks(&day, &preferred_times, hours_per_day);
+        for (day_index, day) in available_days.into_iter().enumerate() {
+            let study_blocks = self.generate_daily_blocks(day_index, &preferred_times, hours_per_day, rng.as_mut());
             
             schedule.push(DailySchedule {
                 day,
@@ -316,43 +916,52 @@ impl TrainedModel {
         schedule
     }
 
-    fn generate_daily_blocks(&self, _day: &str, preferred_times: &[String], daily_hours: f64) -> Vec<StudyBlock> {
+    // Without an RNG the subjects, activities and times rotate through the
+    // lists so the same request always produces the same plan
+    fn generate_daily_blocks(&self, day_index: usize, preferred_times: &[String], daily_hours: f64, mut rng: Option<&mut StdRng>) -> Vec<StudyBlock> {
         let mut blocks = Vec::new();
         let mut remaining_hours = daily_hours;
 
-        let subjects = vec!["Mathematics", "Programming", "Theory", "Practical", "Revision"];
-        let activities = vec!["Reading", "Practice Problems", "Review Notes", "Assignment Work", "Past Papers"];
+        let subjects = ["Mathematics", "Programming", "Theory", "Practical", "Revision"];
+        let activities = ["Reading", "Practice Problems", "Review Notes", "Assignment Work", "Past Papers"];
+        let default_times = ["Morning (8-11 AM)", "Afternoon (2-5 PM)", "Evening (7-10 PM)"];
+        let time_choices = if preferred_times.is_empty() { default_times.len() } else { preferred_times.len() };
 
-        let mut rng = rand::thread_rng();
+        let mut block_index = 0;
 
         while remaining_hours > 0.0 {
-            let duration = if remaining_hours >= 2.0 {
-                rng.gen_range(1.5..2.5)
-            } else {
-                remaining_hours
+            let slot = day_index + block_index;
+
+            let (duration, subject_index, activity_index, time_index) = match rng.as_deref_mut() {
+                Some(rng) => (
+                    if remaining_hours >= 2.0 { rng.gen_range(1.5..2.5) } else { remaining_hours },
+                    rng.gen_range(0..subjects.len()),
+                    rng.gen_range(0..activities.len()),
+                    rng.gen_range(0..time_choices),
+                ),
+                None => (
+                    remaining_hours.min(2.0),
+                    slot % subjects.len(),
+                    (slot + day_index) % activities.len(),
+                    block_index,
+                ),
             };
 
-            let subject = subjects[rng.gen_range(0..subjects.len())].to_string();
-            let activity = activities[rng.gen_range(0..activities.len())].to_string();
-            
             let time = if !preferred_times.is_empty() {
-                preferred_times[rng.gen_range(0..preferred_times.len())].clone()
+                preferred_times[time_index % time_choices].clone()
             } else {
-                match rng.gen_range(0..3) {
-                    0 => "Morning (8-11 AM)".to_string(),
-                    1 => "Afternoon (2-5 PM)".to_string(),
-                    _ => "Evening (7-10 PM)".to_string(),
-                }
+                default_times[time_index % time_choices].to_string()
             };
 
             blocks.push(StudyBlock {
                 time,
-                subject,
-                activity,
+                subject: subjects[subject_index].to_string(),
+                activity: activities[activity_index].to_string(),
                 duration,
             });
 
             remaining_hours -= duration;
+            block_index += 1;
             if remaining_hours < 0.5 {
                 break;
             }
@@ -427,9 +1036,7 @@ impl TrainedModel {
                 "This plan focuses on establishing basic study routines. With {} hours of study and {}% attendance, you should be able to pass your courses.",
                 target_hours, target_attendance
             ),
-            _ => format!(
-                "This personalized study plan is tailored to help you reach your academic goals through consistent effort and improved study habits."
-            )
+            _ => "This personalized study plan is tailored to help you reach your academic goals through consistent effort and improved study habits.".to_string()
         }
     }
 
@@ -442,12 +1049,164 @@ impl TrainedModel {
     }
 }
 
-pub fn train_model() -> ModelInfo {
-    ModelInfo {
-        accuracy: 0.85,
-        features: vec!["study_hours".to_string(), "attendance".to_string()],
-        training_data_size: 1000,
-        model_type: "Logistic Regression".to_string(),
-        last_updated: chrono::Utc::now().to_rfc3339(),
+impl Predictor for TrainedModel {
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        self.calibrate(self.estimator.predict_proba(&self.prepare(features)))
+    }
+
+    fn decision_threshold(&self) -> f64 {
+        self.metadata.threshold.as_ref().map_or(DEFAULT_DECISION_THRESHOLD, |choice| choice.threshold)
+    }
+
+    // Platt scaling is affine in log-odds, so it carries over to the
+    // explanation exactly. Isotonic calibration is not; those explanations
+    // describe the estimator's raw score. Values are the prepared inputs;
+    // `imputed` still marks the features the student did not supply
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        let mut explanation = self.estimator.explain(&self.prepare(features));
+        let imputed = imputed_flags(features);
+        for contribution in &mut explanation.contributions {
+            contribution.imputed = FEATURE_NAMES
+                .iter()
+                .position(|f| *f == contribution.feature)
+                .is_some_and(|j| imputed[j]);
+        }
+        if let Some(Calibrator::Platt { slope, intercept }) = &self.calibrator {
+            explanation.baseline_log_odds = slope * explanation.baseline_log_odds + intercept;
+            for contribution in &mut explanation.contributions {
+                contribution.contribution *= slope;
+            }
+        }
+        explanation
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        let (accuracy, accuracy_method, accuracy_std) = match &self.metadata.validation {
+            Some(v) => (v.accuracy_mean, format!("{}-fold stratified cross-validation", v.folds), Some(v.accuracy_std)),
+            None => (self.metadata.training_accuracy, "training set".to_string(), None),
+        };
+
+        ModelInfo {
+            accuracy,
+            features: self.feature_names.clone(),
+            training_data_size: self.metadata.training_size,
+            model_type: self.family().display_name().to_string(),
+            last_updated: self.metadata.trained_at.clone(),
+            accuracy_method,
+            grade_bands: match self.grade_model {
+                Some(_) => GRADE_BANDS.iter().map(|g| g.to_string()).collect(),
+                None => Vec::new(),
+            },
+            accuracy_std,
+            calibration: self.calibrator.as_ref().map(|c| c.method().display_name().to_string()),
+            calibration_error: self.metadata.validation.as_ref().and_then(|v| v.calibration_error),
+            decision_threshold: self.decision_threshold(),
+            version: None,
+        }
+    }
+}
+
+// Advice targets the supplied feature that pulls the pass likelihood down the most
+fn recommend(prediction: bool, pass_probability: f64, explanation: &PredictionExplanation) -> String {
+    if prediction && pass_probability > 0.8 {
+        return "Continue current study habits".to_string();
+    }
+
+    let weakest = explanation
+        .contributions
+        .iter()
+        .filter(|c| !c.imputed && c.contribution < 0.0)
+        .min_by(|a, b| a.contribution.total_cmp(&b.contribution));
+
+    let advice = match weakest.map(|c| c.feature.as_str()) {
+        Some("study_hours") => "Increase study hours",
+        Some("attendance") => "Improve class attendance",
+        Some("previous_grades") => "Seek academic support to strengthen earlier coursework",
+        Some("sleep_hours") => "Aim for 7-8 hours of sleep each night",
+        _ if prediction => return "Consider slight improvements".to_string(),
+        _ => return "Seek academic support".to_string(),
+    };
+
+    if prediction {
+        format!("Consider slight improvements: {}", advice.to_lowercase())
+    } else {
+        format!("{} - it is lowering your predicted outcome the most", advice)
+    }
+}
+
+// Train on the students CSV with the default configuration
+pub fn train_model(path: &str) -> Result<TrainedModel> {
+    train_model_with_config(path, &TrainingConfig::default())
+}
+
+pub fn train_model_with_config(path: &str, config: &TrainingConfig) -> Result<TrainedModel> {
+    let records = load_dataset(path)?;
+    train_validated_model(&records, config)
+}
+
+// Cross-validate `config` for an honest accuracy estimate, then fit the
+// final model on every record. The out-of-fold predictions double as the
+// held-out data the calibrator is fitted on
+pub fn train_validated_model(records: &[TrainingRecord], config: &TrainingConfig) -> Result<TrainedModel> {
+    let (report, out_of_fold) = cross_validate_with_predictions(records, config, DEFAULT_CV_FOLDS, DEFAULT_CV_SEED)?;
+    let mut model = TrainedModel::fit(records, config)?;
+
+    let (scores, labels): (Vec<f64>, Vec<bool>) = out_of_fold.into_iter().unzip();
+    model.calibrator = config.calibration.and_then(|method| Calibrator::fit(method, &scores, &labels));
+    let calibrated: Vec<f64> = scores.iter().map(|&s| model.calibrate(s)).collect();
+    let calibration_error = model
+        .calibrator
+        .as_ref()
+        .map(|_| expected_calibration_error(&calibrated, &labels, DEFAULT_CALIBRATION_BINS));
+    model.metadata.threshold = Some(tune_threshold(&calibrated, &labels, config.threshold));
+    model.metadata.training_accuracy = training_accuracy(&model, records);
+    if config.bootstrap_models > 0 {
+        model.bootstrap = Some(BootstrapEnsemble::fit(records, config, BOOTSTRAP_SEED)?);
+    }
+
+    model.metadata.validation = Some(ValidationSummary {
+        folds: report.k,
+        accuracy_mean: report.accuracy.mean,
+        accuracy_std: report.accuracy.std_dev,
+        calibration_error,
+    });
+
+    Ok(model)
+}
+
+// Reuse the artifact at `model_path` when there is one, otherwise train from
+// `data_path` and write the artifact so the next start picks it up. An
+// artifact from an older format version is moved aside to
+// `<path>.v<version>.bak` and replaced with a freshly trained one. Any other
+// artifact that fails to load (corrupt, or from a newer build) is an error,
+// so it is never overwritten
+pub fn load_or_train_model(data_path: &str, model_path: Option<&str>, config: &TrainingConfig) -> Result<TrainedModel> {
+    let Some(path) = model_path else {
+        return train_model_with_config(data_path, config);
+    };
+
+    if std::path::Path::new(path).exists() {
+        let json = std::fs::read_to_string(path)?;
+        match TrainedModel::from_json(&json) {
+            Ok(model) => return Ok(model),
+            Err(e) => match artifact_format_version(&json).filter(|&version| version < MODEL_FORMAT_VERSION) {
+                Some(version) => {
+                    let backup = format!("{}.v{}.bak", path, version);
+                    std::fs::rename(path, &backup)?;
+                    eprintln!("⚠️ Model artifact is format version {}, moved to {} and retraining", version, backup);
+                }
+                None => return Err(format!("{}: {}", path, e).into()),
+            },
+        }
     }
-}
\ No newline at end of file
+
+    let model = train_model_with_config(data_path, config)?;
+    model.save(path)?;
+    Ok(model)
+}
+
+// The `format_version` an artifact declares, if it is readable JSON at all
+fn artifact_format_version(json: &str) -> Option<u32> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    value.get("format_version")?.as_u64()?.try_into().ok()
+}
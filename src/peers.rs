@@ -0,0 +1,141 @@
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::TrainingRecord;
+use crate::model::{StudentFeatures, FEATURE_NAMES};
+use crate::predictor::Standardizer;
+
+pub const DEFAULT_PEER_COUNT: usize = 5;
+// Study hours, attendance and previous grades, in `FEATURE_NAMES` order
+const PEER_DIMENSIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerSource {
+    Dataset,
+    RecordedOutcome, // A stored prediction whose outcome was recorded later
+}
+
+// An anonymised past student
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarStudent {
+    pub study_hours: f64,
+    pub attendance: f64,
+    pub previous_grades: f64,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    pub source: PeerSource,
+    pub distance: f64, // In training standard deviations
+}
+
+// The most similar past students, as supporting evidence for a prediction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerEvidence {
+    pub k: usize,
+    pub pass_rate: f64, // Among the peers returned
+    pub compared_on: Vec<String>, // Features the distance used
+    pub students: Vec<SimilarStudent>, // Nearest first
+}
+
+// Labelled students standardized on study hours, attendance and previous
+// grades for nearest-neighbour lookups
+#[derive(Debug, Clone)]
+pub struct PeerIndex {
+    standardizer: Standardizer,
+    rows: Vec<Vec<f64>>, // Standardized
+    students: Vec<SimilarStudent>, // Distance left at 0
+}
+
+impl PeerIndex {
+    pub fn build(dataset: &[TrainingRecord], outcomes: &[TrainingRecord]) -> Self {
+        let labelled = dataset
+            .iter()
+            .map(|r| (r, PeerSource::Dataset))
+            .chain(outcomes.iter().map(|r| (r, PeerSource::RecordedOutcome)));
+
+        let mut raw = Vec::new();
+        let mut students = Vec::new();
+        for (record, source) in labelled {
+            raw.push(peer_vector(&StudentFeatures::from(record)));
+            students.push(SimilarStudent {
+                study_hours: record.hours,
+                attendance: record.attendance,
+                previous_grades: record.previous_grades,
+                passed: record.passed(),
+                grade: record.grade.clone(),
+                source,
+                distance: 0.0,
+            });
+        }
+
+        let standardizer = Standardizer::fit(&raw);
+        Self {
+            rows: raw.iter().map(|r| standardizer.transform(r)).collect(),
+            standardizer,
+            students,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.students.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.students.is_empty()
+    }
+
+    // The k nearest students. Previous grades only count when the student
+    // supplied them, so the default is never matched against real grades.
+    // Ties keep index order so results are deterministic
+    pub fn nearest(&self, features: &StudentFeatures, k: usize) -> PeerEvidence {
+        let query = self.standardizer.transform(&peer_vector(features));
+        let dimensions = if features.previous_grades.is_some() { PEER_DIMENSIONS } else { 2 };
+
+        let mut distances: Vec<(usize, f64)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (i, (0..dimensions).map(|j| (row[j] - query[j]).powi(2)).sum::<f64>().sqrt()))
+            .collect();
+        distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let students: Vec<SimilarStudent> = distances
+            .into_iter()
+            .take(k)
+            .map(|(i, distance)| SimilarStudent { distance, ..self.students[i].clone() })
+            .collect();
+        let passes = students.iter().filter(|s| s.passed).count();
+
+        PeerEvidence {
+            k: students.len(),
+            pass_rate: if students.is_empty() { 0.0 } else { passes as f64 / students.len() as f64 },
+            compared_on: FEATURE_NAMES[..dimensions].iter().map(|f| f.to_string()).collect(),
+            students,
+        }
+    }
+}
+
+fn peer_vector(features: &StudentFeatures) -> Vec<f64> {
+    features.to_vector()[..PEER_DIMENSIONS].to_vec()
+}
+
+// Serves the current index and swaps it when outcomes add new students
+pub struct PeerDirectory {
+    index: RwLock<Arc<PeerIndex>>,
+}
+
+impl PeerDirectory {
+    pub fn new(index: PeerIndex) -> Self {
+        Self { index: RwLock::new(Arc::new(index)) }
+    }
+
+    pub fn current(&self) -> Arc<PeerIndex> {
+        self.index.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, index: PeerIndex) {
+        *self.index.write().unwrap() = Arc::new(index);
+    }
+}
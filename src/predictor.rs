@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+
+use crate::boosting::GradientBoosting;
+use crate::data::{Result, TrainingRecord};
+use crate::knn::KNearestNeighbours;
+use crate::logistic::LogisticRegression;
+use crate::model::{FeatureContribution, ModelInfo, PredictionExplanation, StudentFeatures, TrainingConfig, FEATURE_NAMES};
+use crate::preprocessing::ScalingMethod;
+use crate::threshold::DEFAULT_DECISION_THRESHOLD;
+use crate::tree::DecisionTree;
+
+// Common interface for every model family the server can run. Handlers only
+// talk to models through this trait
+pub trait Predictor: Send + Sync {
+    // Probability that the student passes
+    fn predict_proba(&self, features: &StudentFeatures) -> f64;
+
+    // P(pass) at or above which a student is predicted to pass
+    fn decision_threshold(&self) -> f64 {
+        DEFAULT_DECISION_THRESHOLD
+    }
+
+    fn predict(&self, features: &StudentFeatures) -> (bool, f64) {
+        let probability = self.predict_proba(features);
+
+        let prediction = probability >= self.decision_threshold();
+        let confidence = if prediction { probability } else { 1.0 - probability };
+
+        (prediction, confidence)
+    }
+
+    // Per-feature log-odds contributions relative to an average training student
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation;
+
+    fn metadata(&self) -> ModelInfo;
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFamily {
+    #[default]
+    LogisticRegression,
+    DecisionTree,
+    GradientBoosting,
+    KNearestNeighbours,
+}
+
+impl ModelFamily {
+    // Accepts the serialized name or a short alias ("logistic", "tree",
+    // "boosting", "knn")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "logistic_regression" | "logistic" => Some(ModelFamily::LogisticRegression),
+            "decision_tree" | "tree" => Some(ModelFamily::DecisionTree),
+            "gradient_boosting" | "boosting" | "gbt" => Some(ModelFamily::GradientBoosting),
+            "k_nearest_neighbours" | "knn" => Some(ModelFamily::KNearestNeighbours),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ModelFamily::LogisticRegression => "Logistic Regression",
+            ModelFamily::DecisionTree => "Decision Tree",
+            ModelFamily::GradientBoosting => "Gradient-Boosted Trees",
+            ModelFamily::KNearestNeighbours => "K-Nearest Neighbours",
+        }
+    }
+}
+
+// The fitted model of whichever family was configured. Tagged so model
+// artifacts record which family they hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "family", rename_all = "snake_case")]
+pub enum Estimator {
+    LogisticRegression(LogisticRegression),
+    DecisionTree(DecisionTree),
+    GradientBoosting(GradientBoosting),
+    KNearestNeighbours(KNearestNeighbours),
+}
+
+impl Estimator {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        Ok(match config.family {
+            ModelFamily::LogisticRegression => Estimator::LogisticRegression(LogisticRegression::fit(records, config)?),
+            ModelFamily::DecisionTree => Estimator::DecisionTree(DecisionTree::fit(records, config)?),
+            ModelFamily::GradientBoosting => Estimator::GradientBoosting(GradientBoosting::fit(records, config)?),
+            ModelFamily::KNearestNeighbours => Estimator::KNearestNeighbours(KNearestNeighbours::fit(records, config)?),
+        })
+    }
+
+    pub fn family(&self) -> ModelFamily {
+        match self {
+            Estimator::LogisticRegression(_) => ModelFamily::LogisticRegression,
+            Estimator::DecisionTree(_) => ModelFamily::DecisionTree,
+            Estimator::GradientBoosting(_) => ModelFamily::GradientBoosting,
+            Estimator::KNearestNeighbours(_) => ModelFamily::KNearestNeighbours,
+        }
+    }
+
+    pub fn as_predictor(&self) -> &dyn Predictor {
+        match self {
+            Estimator::LogisticRegression(model) => model,
+            Estimator::DecisionTree(model) => model,
+            Estimator::GradientBoosting(model) => model,
+            Estimator::KNearestNeighbours(model) => model,
+        }
+    }
+
+    // Human-readable if/then rules, for the tree-based families only
+    pub fn rules(&self) -> Option<Vec<String>> {
+        match self {
+            Estimator::DecisionTree(tree) => Some(tree.rules()),
+            Estimator::GradientBoosting(ensemble) => Some(ensemble.rules()),
+            _ => None,
+        }
+    }
+}
+
+impl Predictor for Estimator {
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        self.as_predictor().predict_proba(features)
+    }
+
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        self.as_predictor().explain(features)
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        self.as_predictor().metadata()
+    }
+}
+
+// Per-feature mean and standard deviation of the training rows, which scale
+// inputs by z-score or, for min-max scaling, by the training range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standardizer {
+    pub means: Vec<f64>,
+    pub stds: Vec<f64>,
+    #[serde(default)]
+    scaling: ScalingMethod,
+    #[serde(default)]
+    mins: Vec<f64>, // Min-max scaling only
+    #[serde(default)]
+    ranges: Vec<f64>,
+}
+
+impl Standardizer {
+    pub fn fit(rows: &[Vec<f64>]) -> Self {
+        Self::fit_with(rows, ScalingMethod::Standard)
+    }
+
+    pub fn fit_with(rows: &[Vec<f64>], scaling: ScalingMethod) -> Self {
+        let dims = rows.first().map_or(0, |r| r.len());
+        let n = rows.len().max(1) as f64;
+        let mut means = vec![0.0; dims];
+        let mut stds = vec![0.0; dims];
+
+        for j in 0..dims {
+            let mean = rows.iter().map(|r| r[j]).sum::<f64>() / n;
+            let variance = rows.iter().map(|r| (r[j] - mean).powi(2)).sum::<f64>() / n;
+            means[j] = mean;
+            // Constant columns carry no signal; avoid dividing by zero
+            stds[j] = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+        }
+
+        let (mins, ranges) = match scaling {
+            ScalingMethod::Standard => (Vec::new(), Vec::new()),
+            ScalingMethod::MinMax => (0..dims)
+                .map(|j| {
+                    let min = rows.iter().map(|r| r[j]).fold(f64::MAX, f64::min);
+                    let max = rows.iter().map(|r| r[j]).fold(f64::MIN, f64::max);
+                    (min, if max > min { max - min } else { 1.0 })
+                })
+                .unzip(),
+        };
+
+        Self { means, stds, scaling, mins, ranges }
+    }
+
+    pub fn transform(&self, vector: &[f64]) -> Vec<f64> {
+        let (offsets, scales) = match self.scaling {
+            ScalingMethod::Standard => (&self.means, &self.stds),
+            ScalingMethod::MinMax => (&self.mins, &self.ranges),
+        };
+        vector
+            .iter()
+            .enumerate()
+            .map(|(j, x)| (x - offsets[j]) / scales[j])
+            .collect()
+    }
+}
+
+pub fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+// Log-odds, with probabilities kept away from 0 and 1 so the result is finite
+pub fn logit(p: f64) -> f64 {
+    let p = p.clamp(1e-6, 1.0 - 1e-6);
+    (p / (1.0 - p)).ln()
+}
+
+// Which features fell back to their documented defaults, in FEATURE_NAMES order
+pub fn imputed_flags(features: &StudentFeatures) -> [bool; 4] {
+    [false, false, features.previous_grades.is_none(), features.sleep_hours.is_none()]
+}
+
+// Model-agnostic explanation: each feature's contribution is the change in
+// log-odds when it is reset to its training mean. Unlike the logistic
+// regression breakdown, these need not add up exactly to the prediction
+pub fn occlusion_explanation(predictor: &dyn Predictor, features: &StudentFeatures, means: &[f64]) -> PredictionExplanation {
+    let vector = features.to_vector();
+    let log_odds = logit(predictor.predict_proba(features));
+    let imputed = imputed_flags(features);
+
+    let mut contributions: Vec<FeatureContribution> = (0..FEATURE_NAMES.len())
+        .map(|j| {
+            let mut reset = vector.clone();
+            reset[j] = means[j];
+            FeatureContribution {
+                feature: FEATURE_NAMES[j].to_string(),
+                value: vector[j],
+                contribution: log_odds - logit(predictor.predict_proba(&StudentFeatures::from_vector(&reset))),
+                imputed: imputed[j],
+            }
+        })
+        .collect();
+    contributions.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+    PredictionExplanation {
+        baseline_log_odds: logit(predictor.predict_proba(&StudentFeatures::from_vector(means))),
+        contributions,
+    }
+}
+
+// Fraction of `records` a predictor labels correctly at its decision threshold
+pub fn training_accuracy(predictor: &dyn Predictor, records: &[TrainingRecord]) -> f64 {
+    if records.is_empty() {
+        return 0.0;
+    }
+
+    let correct = records
+        .iter()
+        .filter(|r| predictor.predict(&StudentFeatures::from(*r)).0 == r.passed())
+        .count();
+    correct as f64 / records.len() as f64
+}
+
+// Metadata shared by the bare model families
+pub(crate) fn family_info(family: ModelFamily, training_size: usize, training_accuracy: f64, trained_at: &str) -> ModelInfo {
+    ModelInfo {
+        accuracy: training_accuracy,
+        features: FEATURE_NAMES.iter().map(|f| f.to_string()).collect(),
+        training_data_size: training_size,
+        model_type: family.display_name().to_string(),
+        last_updated: trained_at.to_string(),
+        accuracy_method: "training set".to_string(),
+        grade_bands: Vec::new(),
+        accuracy_std: None,
+        calibration: None,
+        calibration_error: None,
+        decision_threshold: DEFAULT_DECISION_THRESHOLD,
+        version: None,
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::StudentFeatures;
+
+// How logistic regression and KNN scale features before fitting. Trees split
+// on raw values and are unaffected
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMethod {
+    #[default]
+    Standard, // Zero mean, unit variance
+    MinMax, // Training range mapped to 0..1
+}
+
+impl ScalingMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "standard" | "standardize" | "z-score" => Some(ScalingMethod::Standard),
+            "minmax" | "min-max" | "min_max" => Some(ScalingMethod::MinMax),
+            _ => None,
+        }
+    }
+}
+
+// What a missing optional feature (previous grades, sleep hours) is replaced with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImputationStrategy {
+    #[default]
+    Defaults, // `DEFAULT_PREVIOUS_GRADES` and `DEFAULT_SLEEP_HOURS`
+    Mean, // Of the training data
+    Median,
+}
+
+impl ImputationStrategy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "defaults" | "default" | "constant" => Some(ImputationStrategy::Defaults),
+            "mean" => Some(ImputationStrategy::Mean),
+            "median" => Some(ImputationStrategy::Median),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct PreprocessingConfig {
+    #[serde(default)]
+    pub scaling: ScalingMethod,
+    #[serde(default)]
+    pub imputation: ImputationStrategy,
+    #[serde(default)]
+    pub clip: bool, // Clamp inputs to the training range before scoring
+}
+
+// Imputation and clipping fitted on the training rows and stored in the
+// artifact, so serving fills and bounds inputs exactly as training saw them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preprocessor {
+    imputation: ImputationStrategy,
+    fill_values: Vec<f64>, // Per feature in `FEATURE_NAMES` order
+    #[serde(default)]
+    bounds: Option<Vec<(f64, f64)>>, // Training minimum and maximum, when clipping
+}
+
+impl Default for Preprocessor {
+    // Matches artifacts saved before the pipeline existed
+    fn default() -> Self {
+        Self {
+            imputation: ImputationStrategy::Defaults,
+            // Only the optional features' documented defaults are ever used
+            fill_values: StudentFeatures::new(0.0, 0.0).to_vector(),
+            bounds: None,
+        }
+    }
+}
+
+impl Preprocessor {
+    pub fn fit(rows: &[Vec<f64>], config: &PreprocessingConfig) -> Self {
+        let column = |j: usize| -> Vec<f64> { rows.iter().map(|r| r[j]).collect() };
+        let dims = rows.first().map_or(0, |r| r.len());
+
+        let fill_values = match config.imputation {
+            ImputationStrategy::Defaults => Self::default().fill_values,
+            ImputationStrategy::Mean => (0..dims).map(|j| mean(&column(j))).collect(),
+            ImputationStrategy::Median => (0..dims).map(|j| median(&column(j))).collect(),
+        };
+        let bounds = config.clip.then(|| {
+            (0..dims)
+                .map(|j| {
+                    let values = column(j);
+                    (values.iter().cloned().fold(f64::MAX, f64::min), values.iter().cloned().fold(f64::MIN, f64::max))
+                })
+                .collect()
+        });
+
+        Self {
+            imputation: config.imputation,
+            fill_values,
+            bounds,
+        }
+    }
+
+    pub fn imputation(&self) -> ImputationStrategy {
+        self.imputation
+    }
+
+    // Missing optional features filled in; supplied values are left as they are
+    pub fn impute(&self, features: &StudentFeatures) -> StudentFeatures {
+        StudentFeatures {
+            previous_grades: Some(features.previous_grades.unwrap_or(self.fill_values[2])),
+            sleep_hours: Some(features.sleep_hours.unwrap_or(self.fill_values[3])),
+            ..features.clone()
+        }
+    }
+
+    // Missing optional features filled in and, when clipping, every feature
+    // clamped to the training range. The result has every feature supplied
+    pub fn apply(&self, features: &StudentFeatures) -> StudentFeatures {
+        let mut vector = vec![
+            features.hours,
+            features.attendance,
+            features.previous_grades.unwrap_or(self.fill_values[2]),
+            features.sleep_hours.unwrap_or(self.fill_values[3]),
+        ];
+        if let Some(bounds) = &self.bounds {
+            for (value, &(low, high)) in vector.iter_mut().zip(bounds) {
+                *value = value.clamp(low, high);
+            }
+        }
+        StudentFeatures::from_vector(&vector)
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
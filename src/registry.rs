@@ -0,0 +1,195 @@
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::database::{Database, ModelVersion};
+use crate::model::TrainedModel;
+use crate::predictor::Predictor;
+
+#[derive(Debug)]
+pub enum RegistryError {
+    NotFound(String),
+    NoPreviousVersion,
+    AlreadyActive(String),
+    InvalidArtifact(String),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::NotFound(version) => write!(f, "Model version {} not found", version),
+            RegistryError::NoPreviousVersion => write!(f, "No previously active model version to roll back to"),
+            RegistryError::AlreadyActive(version) => write!(f, "Model version {} is already active", version),
+            RegistryError::InvalidArtifact(msg) => write!(f, "Invalid model artifact: {}", msg),
+            RegistryError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl From<sqlx::Error> for RegistryError {
+    fn from(e: sqlx::Error) -> Self {
+        RegistryError::Database(e)
+    }
+}
+
+struct ActiveModel {
+    version: String,
+    model: Arc<TrainedModel>,
+}
+
+// Serves the active model version and swaps it in place when another
+// version is activated, so handlers never need a restart. An optional
+// challenger version is scored silently next to it for shadow evaluation
+pub struct ModelRegistry {
+    active: RwLock<ActiveModel>,
+    challenger: RwLock<Option<ActiveModel>>,
+}
+
+impl ModelRegistry {
+    // Resume the version marked active in the database, or register and
+    // activate `fallback` when the registry is empty
+    pub async fn bootstrap(db: &Database, fallback: TrainedModel) -> Result<Self, RegistryError> {
+        let (version, model) = match db.get_active_model_version().await? {
+            Some(active) => {
+                let model = parse_artifact(&active)?;
+                (active.version, model)
+            }
+            None => {
+                let registered = register_model(db, &fallback).await?;
+                db.activate_model_version(&registered.version).await?;
+                (registered.version, fallback)
+            }
+        };
+
+        Ok(Self {
+            active: RwLock::new(ActiveModel {
+                version,
+                model: Arc::new(model),
+            }),
+            challenger: RwLock::new(None),
+        })
+    }
+
+    pub fn current(&self) -> Arc<TrainedModel> {
+        self.active.read().unwrap().model.clone()
+    }
+
+    pub fn active_version(&self) -> String {
+        self.active.read().unwrap().version.clone()
+    }
+
+    // Store a model as a new, inactive version
+    pub async fn register(&self, db: &Database, model: &TrainedModel) -> Result<ModelVersion, RegistryError> {
+        register_model(db, model).await
+    }
+
+    pub async fn activate(&self, db: &Database, version: &str) -> Result<ModelVersion, RegistryError> {
+        let mut record = db
+            .get_model_version(version)
+            .await?
+            .ok_or_else(|| RegistryError::NotFound(version.to_string()))?;
+        let model = parse_artifact(&record)?;
+
+        db.activate_model_version(version).await?;
+        self.swap_in(&record, model);
+
+        record.is_active = true;
+        Ok(record)
+    }
+
+    // Serve `model` as `record`'s version
+    fn swap_in(&self, record: &ModelVersion, model: TrainedModel) {
+        let mut active = self.active.write().unwrap();
+        active.version = record.version.clone();
+        active.model = Arc::new(model);
+
+        // A promoted challenger has nothing left to shadow
+        let mut challenger = self.challenger.write().unwrap();
+        if challenger.as_ref().is_some_and(|c| c.version == record.version) {
+            *challenger = None;
+        }
+    }
+
+    // Start shadowing the active version with `version`, replacing any
+    // current challenger
+    pub async fn set_challenger(&self, db: &Database, version: &str) -> Result<ModelVersion, RegistryError> {
+        if version == self.active_version() {
+            return Err(RegistryError::AlreadyActive(version.to_string()));
+        }
+
+        let record = db
+            .get_model_version(version)
+            .await?
+            .ok_or_else(|| RegistryError::NotFound(version.to_string()))?;
+        let model = parse_artifact(&record)?;
+
+        *self.challenger.write().unwrap() = Some(ActiveModel {
+            version: record.version.clone(),
+            model: Arc::new(model),
+        });
+
+        Ok(record)
+    }
+
+    // Stop shadowing; returns the version that was the challenger
+    pub fn clear_challenger(&self) -> Option<String> {
+        self.challenger.write().unwrap().take().map(|c| c.version)
+    }
+
+    pub fn challenger(&self) -> Option<(String, Arc<TrainedModel>)> {
+        self.challenger
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|c| (c.version.clone(), c.model.clone()))
+    }
+
+    // Reactivate whichever version was serving before the current one.
+    // Repeated rollbacks keep walking back through the activation history
+    pub async fn rollback(&self, db: &Database) -> Result<ModelVersion, RegistryError> {
+        let previous = db
+            .previous_active_version()
+            .await?
+            .ok_or(RegistryError::NoPreviousVersion)?;
+
+        let mut record = db
+            .get_model_version(&previous)
+            .await?
+            .ok_or_else(|| RegistryError::NotFound(previous.clone()))?;
+        let model = parse_artifact(&record)?;
+
+        db.rollback_to_version(&previous).await?;
+        self.swap_in(&record, model);
+
+        record.is_active = true;
+        Ok(record)
+    }
+}
+
+pub fn parse_artifact(version: &ModelVersion) -> Result<TrainedModel, RegistryError> {
+    TrainedModel::from_json(&version.artifact)
+        .map_err(|e| RegistryError::InvalidArtifact(format!("{} ({})", version.version, e)))
+}
+
+async fn register_model(db: &Database, model: &TrainedModel) -> Result<ModelVersion, RegistryError> {
+    let info = model.metadata();
+    let artifact = model
+        .to_json()
+        .map_err(|e| RegistryError::InvalidArtifact(e.to_string()))?;
+
+    let mut record = ModelVersion {
+        id: 0,
+        version: String::new(), // Assigned on save
+        accuracy: info.accuracy,
+        features_used: info.features.join(","),
+        training_data_size: info.training_data_size as i64,
+        is_active: false,
+        artifact,
+        created_at: chrono::Utc::now(),
+    };
+    (record.id, record.version) = db.save_model_version(&record).await?;
+
+    Ok(record)
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{stratified_train_test_split, Result, TrainingRecord};
+use crate::database::{StudentOutcome, StudentRecord};
+use crate::evaluation::{evaluate_model, EvaluationReport};
+use crate::model::{train_validated_model, StudentFeatures, TrainedModel, TrainingConfig};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainOptions {
+    #[serde(default = "default_test_fraction")]
+    pub test_fraction: f64,
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+}
+
+fn default_test_fraction() -> f64 {
+    0.3
+}
+
+fn default_seed() -> u64 {
+    42
+}
+
+impl Default for RetrainOptions {
+    fn default() -> Self {
+        Self {
+            test_fraction: default_test_fraction(),
+            seed: default_seed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrainReport {
+    pub base_records: usize,
+    pub outcome_records: usize, // Labelled students merged in
+    pub train_size: usize,
+    pub holdout_size: usize,
+    pub candidate: EvaluationReport,
+    pub active: EvaluationReport,
+    pub improved: bool, // The candidate beat the active model on the holdout
+}
+
+// Training records for labelled predictions. Features the student did not
+// supply are filled in by `model`'s fitted imputation, the values it
+// predicted with
+pub fn outcome_records(labelled: &[(StudentRecord, StudentOutcome)], model: &TrainedModel) -> Vec<TrainingRecord> {
+    labelled
+        .iter()
+        .map(|(prediction, outcome)| {
+            let features = model.impute(&StudentFeatures {
+                hours: prediction.study_hours,
+                attendance: prediction.attendance,
+                previous_grades: prediction.previous_grades,
+                sleep_hours: prediction.sleep_hours,
+            });
+            TrainingRecord {
+                hours: features.hours,
+                attendance: features.attendance,
+                previous_grades: features.previous_grades.unwrap_or_default(),
+                sleep_hours: features.sleep_hours.unwrap_or_default(),
+                pass: if outcome.passed { 1.0 } else { 0.0 },
+                grade: outcome.grade.clone(),
+                groups: BTreeMap::new(),
+            }
+        })
+        .collect()
+}
+
+// Trains a candidate on the base data plus the labelled outcomes and scores
+// it against the active model on a stratified holdout of the merged records.
+// The active model may have been trained on some holdout rows, which only
+// makes the comparison harder for the candidate. The returned model is then
+// refitted on every merged record, so it was trained on exactly the base data
+// plus the outcomes recorded up to `outcomes_until`
+pub fn retrain_candidate(
+    base: &[TrainingRecord],
+    outcomes: &[TrainingRecord],
+    outcomes_until: Option<DateTime<Utc>>,
+    active: &TrainedModel,
+    config: &TrainingConfig,
+    options: &RetrainOptions,
+) -> Result<(TrainedModel, RetrainReport)> {
+    let merged: Vec<TrainingRecord> = base.iter().chain(outcomes).cloned().collect();
+    let (train, holdout) = stratified_train_test_split(&merged, options.test_fraction, options.seed);
+    if holdout.is_empty() {
+        return Err("Not enough records to hold out".into());
+    }
+
+    let candidate_report = evaluate_model(&train_validated_model(&train, config)?, &holdout);
+    let active_report = evaluate_model(active, &holdout);
+    let mut candidate = train_validated_model(&merged, config)?;
+    candidate.set_outcomes(outcomes.len(), outcomes_until);
+
+    let report = RetrainReport {
+        base_records: base.len(),
+        outcome_records: outcomes.len(),
+        train_size: train.len(),
+        holdout_size: holdout.len(),
+        improved: beats(&candidate_report, &active_report),
+        candidate: candidate_report,
+        active: active_report,
+    };
+
+    Ok((candidate, report))
+}
+
+// Higher accuracy wins; equal accuracy goes to the lower log loss
+pub fn beats(candidate: &EvaluationReport, active: &EvaluationReport) -> bool {
+    if candidate.accuracy != active.accuracy {
+        candidate.accuracy > active.accuracy
+    } else {
+        candidate.log_loss < active.log_loss
+    }
+}
@@ -0,0 +1,274 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::Calibrator;
+use crate::data::{Result, TrainingRecord};
+use crate::evaluation::{cross_validate_with_predictions, evaluate_probabilities, DEFAULT_CV_FOLDS, DEFAULT_CV_SEED};
+use crate::model::TrainingConfig;
+use crate::predictor::ModelFamily;
+use crate::threshold::ThresholdObjective;
+
+// Limits on a client-supplied search, so one request cannot queue an
+// unbounded amount of refitting
+pub const MAX_SEARCH_VALUES: usize = 10; // Per hyperparameter list
+pub const MAX_SEARCH_CONFIGS: usize = 100; // Model configs cross-validated per search
+pub const MAX_SEARCH_FOLDS: usize = 10;
+pub const MAX_SEARCH_DEPTH: usize = 10;
+pub const MAX_SEARCH_ESTIMATORS: usize = 200;
+
+// Values to try for each hyperparameter. Only the ones a family uses are
+// combined for it, so a tree is never refitted for every learning rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSpace {
+    pub families: Vec<ModelFamily>,
+    pub learning_rates: Vec<f64>,
+    pub l2_penalties: Vec<f64>,
+    pub max_depths: Vec<usize>,
+    pub min_samples_leaf: Vec<usize>,
+    pub n_estimators: Vec<usize>,
+    pub k_neighbours: Vec<usize>,
+    pub thresholds: Vec<f64>, // Decision thresholds; scored without refitting
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        Self {
+            families: vec![
+                ModelFamily::LogisticRegression,
+                ModelFamily::DecisionTree,
+                ModelFamily::GradientBoosting,
+                ModelFamily::KNearestNeighbours,
+            ],
+            learning_rates: vec![0.05, 0.1, 0.3],
+            l2_penalties: vec![0.0, 0.01, 0.1],
+            max_depths: vec![2, 3, 4],
+            min_samples_leaf: vec![1, 2],
+            n_estimators: vec![25, 50],
+            k_neighbours: vec![1, 3, 5],
+            thresholds: vec![0.4, 0.5, 0.6],
+        }
+    }
+}
+
+impl SearchSpace {
+    // Rejects lists longer than `MAX_SEARCH_VALUES` and values a model cannot
+    // be trained with, or that would make a single fit unboundedly slow
+    pub fn validate(&self) -> Result<()> {
+        let lengths = [
+            self.families.len(),
+            self.learning_rates.len(),
+            self.l2_penalties.len(),
+            self.max_depths.len(),
+            self.min_samples_leaf.len(),
+            self.n_estimators.len(),
+            self.k_neighbours.len(),
+            self.thresholds.len(),
+        ];
+        if lengths.iter().any(|&n| n > MAX_SEARCH_VALUES) {
+            return Err(format!("Each hyperparameter can list at most {} values", MAX_SEARCH_VALUES).into());
+        }
+        if !self.learning_rates.iter().all(|&rate| rate > 0.0 && rate <= 1.0) {
+            return Err("Learning rates must be above 0 and at most 1".into());
+        }
+        if !self.l2_penalties.iter().all(|&penalty| (0.0..f64::INFINITY).contains(&penalty)) {
+            return Err("L2 penalties must be finite and not negative".into());
+        }
+        if !self.max_depths.iter().all(|depth| (1..=MAX_SEARCH_DEPTH).contains(depth)) {
+            return Err(format!("Max depths must be between 1 and {}", MAX_SEARCH_DEPTH).into());
+        }
+        if !self.n_estimators.iter().all(|n| (1..=MAX_SEARCH_ESTIMATORS).contains(n)) {
+            return Err(format!("Estimator counts must be between 1 and {}", MAX_SEARCH_ESTIMATORS).into());
+        }
+        if self.min_samples_leaf.contains(&0) || self.k_neighbours.contains(&0) {
+            return Err("Leaf sizes and neighbour counts must be at least 1".into());
+        }
+        if !self.thresholds.iter().all(|threshold| (0.0..=1.0).contains(threshold)) {
+            return Err("Thresholds must be between 0 and 1".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMetric {
+    #[default]
+    Accuracy,
+    F1Score,
+    RocAuc,
+    LogLoss, // Lower is better
+}
+
+impl SearchMetric {
+    fn higher_is_better(&self) -> bool {
+        !matches!(self, SearchMetric::LogLoss)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOptions {
+    #[serde(default)]
+    pub trials: Option<usize>, // Random search over this many model configs; None searches the full grid
+    #[serde(default)]
+    pub metric: SearchMetric,
+    #[serde(default = "default_folds")]
+    pub folds: usize,
+    #[serde(default = "default_seed")]
+    pub seed: u64, // Drives both the random sampling and the folds
+}
+
+fn default_folds() -> usize {
+    DEFAULT_CV_FOLDS
+}
+
+fn default_seed() -> u64 {
+    DEFAULT_CV_SEED
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            trials: None,
+            metric: SearchMetric::default(),
+            folds: default_folds(),
+            seed: default_seed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub config: TrainingConfig,
+    pub score: f64, // The chosen metric
+    pub accuracy: f64,
+    pub f1_score: f64,
+    pub roc_auc: f64,
+    pub log_loss: f64,
+}
+
+// Every model config the space describes, on top of `base` (which supplies
+// calibration, bootstrap size and anything not searched)
+pub fn model_configs(space: &SearchSpace, base: &TrainingConfig) -> Vec<TrainingConfig> {
+    let mut configs = Vec::new();
+    let with_family = |family| TrainingConfig { family, ..base.clone() };
+
+    for &family in &space.families {
+        match family {
+            ModelFamily::LogisticRegression => {
+                for &learning_rate in &space.learning_rates {
+                    for &l2_penalty in &space.l2_penalties {
+                        configs.push(TrainingConfig { learning_rate, l2_penalty, ..with_family(family) });
+                    }
+                }
+            }
+            ModelFamily::DecisionTree => {
+                for &max_depth in &space.max_depths {
+                    for &min_samples_leaf in &space.min_samples_leaf {
+                        configs.push(TrainingConfig { max_depth, min_samples_leaf, ..with_family(family) });
+                    }
+                }
+            }
+            ModelFamily::GradientBoosting => {
+                for &learning_rate in &space.learning_rates {
+                    for &max_depth in &space.max_depths {
+                        for &n_estimators in &space.n_estimators {
+                            configs.push(TrainingConfig { learning_rate, max_depth, n_estimators, ..with_family(family) });
+                        }
+                    }
+                }
+            }
+            ModelFamily::KNearestNeighbours => {
+                for &k_neighbours in &space.k_neighbours {
+                    configs.push(TrainingConfig { k_neighbours, ..with_family(family) });
+                }
+            }
+        }
+    }
+
+    configs
+}
+
+// Cross-validates each model config once and scores its out-of-fold
+// predictions at every threshold in the space. The predictions are
+// calibrated first, as `train_validated_model` would, so thresholds mean the
+// same here as in the served model. Returns the leaderboard, best first.
+// Spaces with more than `MAX_SEARCH_CONFIGS` model configs must be sampled
+// with `trials`
+pub fn hyperparameter_search(
+    records: &[TrainingRecord],
+    base: &TrainingConfig,
+    space: &SearchSpace,
+    options: &SearchOptions,
+) -> Result<Vec<LeaderboardEntry>> {
+    space.validate()?;
+    if !(2..=MAX_SEARCH_FOLDS).contains(&options.folds) {
+        return Err(format!("Folds must be between 2 and {}", MAX_SEARCH_FOLDS).into());
+    }
+
+    let mut configs = model_configs(space, base);
+    if let Some(trials) = options.trials {
+        configs.shuffle(&mut StdRng::seed_from_u64(options.seed));
+        configs.truncate(trials);
+    }
+    if configs.is_empty() || space.thresholds.is_empty() {
+        return Err("The search space is empty".into());
+    }
+    if configs.len() > MAX_SEARCH_CONFIGS {
+        return Err(format!(
+            "The search space has {} model configs; narrow it or set trials to at most {}",
+            configs.len(),
+            MAX_SEARCH_CONFIGS
+        ).into());
+    }
+
+    let mut entries = Vec::new();
+    for config in configs {
+        let (_, out_of_fold) = cross_validate_with_predictions(records, &config, options.folds, options.seed)?;
+        let (scores, labels): (Vec<f64>, Vec<bool>) = out_of_fold.into_iter().unzip();
+        let probabilities: Vec<f64> = match config.calibration.and_then(|method| Calibrator::fit(method, &scores, &labels)) {
+            Some(calibrator) => scores.iter().map(|&s| calibrator.apply(s)).collect(),
+            None => scores,
+        };
+
+        for &threshold in &space.thresholds {
+            let report = evaluate_probabilities(&probabilities, &labels, threshold);
+            let score = match options.metric {
+                SearchMetric::Accuracy => report.accuracy,
+                SearchMetric::F1Score => report.f1_score,
+                SearchMetric::RocAuc => report.roc_auc,
+                SearchMetric::LogLoss => report.log_loss,
+            };
+
+            entries.push(LeaderboardEntry {
+                rank: 0,
+                config: TrainingConfig {
+                    threshold: ThresholdObjective::Fixed { threshold },
+                    ..config.clone()
+                },
+                score,
+                accuracy: report.accuracy,
+                f1_score: report.f1_score,
+                roc_auc: report.roc_auc,
+                log_loss: report.log_loss,
+            });
+        }
+    }
+
+    // Stable sort, so ties keep the order the space lists them in
+    entries.sort_by(|a, b| {
+        if options.metric.higher_is_better() {
+            b.score.total_cmp(&a.score)
+        } else {
+            a.score.total_cmp(&b.score)
+        }
+    });
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.rank = i + 1;
+    }
+
+    Ok(entries)
+}
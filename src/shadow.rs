@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::ShadowComparison;
+
+// How a challenger shadowing production compares with the champion on the
+// same requests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShadowReport {
+    pub challenger_version: String,
+    pub champion_versions: Vec<String>, // Active versions while the challenger ran
+    pub compared: usize,
+    pub agreement_rate: f64, // Same Pass/Fail call
+    pub mean_probability_gap: f64, // Mean absolute difference in P(pass)
+    pub labelled: usize, // Compared predictions whose outcome is known
+    pub champion_accuracy: Option<f64>, // On the labelled predictions
+    pub challenger_accuracy: Option<f64>,
+}
+
+pub fn shadow_report(challenger_version: &str, comparisons: &[ShadowComparison]) -> ShadowReport {
+    let mut champion_versions: Vec<String> = comparisons.iter().filter_map(|c| c.champion_version.clone()).collect();
+    champion_versions.sort();
+    champion_versions.dedup();
+
+    let labelled: Vec<(&ShadowComparison, bool)> = comparisons
+        .iter()
+        .filter_map(|c| c.actual_pass.map(|actual| (c, actual)))
+        .collect();
+    let accuracy = |correct: usize| {
+        if labelled.is_empty() {
+            None
+        } else {
+            Some(correct as f64 / labelled.len() as f64)
+        }
+    };
+
+    ShadowReport {
+        challenger_version: challenger_version.to_string(),
+        champion_versions,
+        compared: comparisons.len(),
+        agreement_rate: mean(comparisons.iter().map(|c| if c.champion_pass == c.challenger_pass { 1.0 } else { 0.0 })),
+        mean_probability_gap: mean(comparisons.iter().map(|c| (c.champion_confidence - c.challenger_confidence).abs())),
+        labelled: labelled.len(),
+        champion_accuracy: accuracy(labelled.iter().filter(|(c, actual)| c.champion_pass == *actual).count()),
+        challenger_accuracy: accuracy(labelled.iter().filter(|(c, actual)| c.challenger_pass == *actual).count()),
+    }
+}
+
+// 0 for no values
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::Result;
+use crate::evaluation::confusion_matrix;
+
+pub const DEFAULT_DECISION_THRESHOLD: f64 = 0.5;
+
+// What a missed at-risk student (predicted Pass, actually failed) costs
+// relative to a false alarm (predicted Fail, actually passed)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CostMatrix {
+    pub missed_at_risk: f64,
+    pub false_alarm: f64,
+}
+
+impl Default for CostMatrix {
+    fn default() -> Self {
+        Self {
+            missed_at_risk: 5.0,
+            false_alarm: 1.0,
+        }
+    }
+}
+
+// How the P(pass) cutoff is chosen. Students below it are predicted to fail
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "objective", rename_all = "snake_case")]
+pub enum ThresholdObjective {
+    Fixed { threshold: f64 },
+    // Lowest cutoff that still flags at least `recall` of the students who
+    // go on to fail, so as few passing students as possible are flagged
+    TargetRecall { recall: f64 },
+    MinimizeCost { costs: CostMatrix },
+}
+
+impl ThresholdObjective {
+    // Cutoffs and recall targets are probabilities; costs must be finite
+    // and not negative. NaN is never in range
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ThresholdObjective::Fixed { threshold } if !(0.0..=1.0).contains(threshold) => {
+                Err("threshold must be between 0 and 1".into())
+            }
+            ThresholdObjective::TargetRecall { recall } if !(0.0..=1.0).contains(recall) => {
+                Err("recall must be between 0 and 1".into())
+            }
+            ThresholdObjective::MinimizeCost { costs }
+                if ![costs.missed_at_risk, costs.false_alarm].iter().all(|c| (0.0..f64::INFINITY).contains(c)) =>
+            {
+                Err("costs must be finite and not negative".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ThresholdObjective {
+    fn default() -> Self {
+        ThresholdObjective::Fixed { threshold: DEFAULT_DECISION_THRESHOLD }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdChoice {
+    pub threshold: f64,
+    pub objective: ThresholdObjective,
+    pub at_risk_recall: f64, // Share of failing students predicted to fail
+    pub false_alarm_rate: f64, // Share of passing students predicted to fail
+    pub cost: f64, // Under `CostMatrix::default()` unless the objective sets its own
+    pub sample_size: usize,
+}
+
+// Picks a cutoff on held-out P(pass) for the objective. Candidates are the
+// midpoints between neighbouring distinct probabilities plus both ends, so
+// every achievable split of the students is considered. When the target
+// recall cannot be met, the cutoff flagging every student is returned
+pub fn tune_threshold(probabilities: &[f64], labels: &[bool], objective: ThresholdObjective) -> ThresholdChoice {
+    let costs = match objective {
+        ThresholdObjective::MinimizeCost { costs } => costs,
+        _ => CostMatrix::default(),
+    };
+
+    let threshold = match objective {
+        ThresholdObjective::Fixed { threshold } => threshold,
+        ThresholdObjective::TargetRecall { recall } => candidate_thresholds(probabilities)
+            .into_iter()
+            .find(|&t| at_risk_recall(probabilities, labels, t) >= recall)
+            .unwrap_or(1.0),
+        ThresholdObjective::MinimizeCost { costs } => candidate_thresholds(probabilities)
+            .into_iter()
+            .map(|t| (t, cost(probabilities, labels, t, &costs)))
+            // Equal costs go to the cutoff nearest the default
+            .min_by(|a, b| {
+                a.1.total_cmp(&b.1).then_with(|| {
+                    (a.0 - DEFAULT_DECISION_THRESHOLD).abs().total_cmp(&(b.0 - DEFAULT_DECISION_THRESHOLD).abs())
+                })
+            })
+            .map_or(DEFAULT_DECISION_THRESHOLD, |(t, _)| t),
+    };
+
+    let matrix = confusion_matrix(probabilities, labels, threshold);
+    let passes = matrix.true_positives + matrix.false_negatives;
+
+    ThresholdChoice {
+        threshold,
+        objective,
+        at_risk_recall: at_risk_recall(probabilities, labels, threshold),
+        false_alarm_rate: if passes == 0 { 0.0 } else { matrix.false_negatives as f64 / passes as f64 },
+        cost: cost(probabilities, labels, threshold, &costs),
+        sample_size: labels.len(),
+    }
+}
+
+// Ascending, so the first one meeting a recall target flags the fewest students
+fn candidate_thresholds(probabilities: &[f64]) -> Vec<f64> {
+    let mut sorted = probabilities.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted.dedup();
+
+    let mut candidates = vec![0.0];
+    candidates.extend(sorted.windows(2).map(|w| (w[0] + w[1]) / 2.0));
+    candidates.push(1.0);
+    candidates
+}
+
+fn at_risk_recall(probabilities: &[f64], labels: &[bool], threshold: f64) -> f64 {
+    let matrix = confusion_matrix(probabilities, labels, threshold);
+    let fails = matrix.true_negatives + matrix.false_positives;
+    if fails == 0 {
+        1.0
+    } else {
+        matrix.true_negatives as f64 / fails as f64
+    }
+}
+
+fn cost(probabilities: &[f64], labels: &[bool], threshold: f64, costs: &CostMatrix) -> f64 {
+    let matrix = confusion_matrix(probabilities, labels, threshold);
+    matrix.false_positives as f64 * costs.missed_at_risk + matrix.false_negatives as f64 * costs.false_alarm
+}
@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Result, TrainingRecord};
+use crate::model::{FeatureContribution, ModelInfo, PredictionExplanation, StudentFeatures, TrainingConfig, FEATURE_NAMES};
+use crate::predictor::{family_info, imputed_flags, logit, training_accuracy, ModelFamily, Predictor};
+
+// Nodes live in one vector and refer to their children by index; the root is
+// node 0. Every node keeps the smoothed pass rate of the students reaching it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TreeNode {
+    Split {
+        feature: usize,
+        threshold: f64, // Students with value <= threshold go left
+        left: usize,
+        right: usize,
+        probability: f64,
+        samples: usize,
+    },
+    Leaf {
+        probability: f64,
+        samples: usize,
+    },
+}
+
+impl TreeNode {
+    pub fn probability(&self) -> f64 {
+        match self {
+            TreeNode::Split { probability, .. } | TreeNode::Leaf { probability, .. } => *probability,
+        }
+    }
+}
+
+// CART classification tree grown on raw feature values with Gini impurity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionTree {
+    nodes: Vec<TreeNode>,
+    training_size: usize,
+    training_accuracy: f64,
+    #[serde(default)]
+    trained_at: String,
+}
+
+impl DecisionTree {
+    pub fn fit(records: &[TrainingRecord], config: &TrainingConfig) -> Result<Self> {
+        if records.is_empty() {
+            return Err("Cannot train a model without records".into());
+        }
+
+        let rows: Vec<Vec<f64>> = records.iter().map(|r| StudentFeatures::from(r).to_vector()).collect();
+        let labels: Vec<bool> = records.iter().map(|r| r.passed()).collect();
+
+        let mut nodes = Vec::new();
+        let indices: Vec<usize> = (0..rows.len()).collect();
+        grow(&mut nodes, &rows, &labels, indices, 0, config);
+
+        let mut tree = Self {
+            nodes,
+            training_size: records.len(),
+            training_accuracy: 0.0,
+            trained_at: chrono::Utc::now().to_rfc3339(),
+        };
+        tree.training_accuracy = training_accuracy(&tree, records);
+
+        Ok(tree)
+    }
+
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    // One human-readable if/then rule per leaf, left branches first
+    pub fn rules(&self) -> Vec<String> {
+        let split_of = |index: usize| match &self.nodes[index] {
+            TreeNode::Split { feature, threshold, left, right, .. } => Some((*feature, *threshold, *left, *right)),
+            TreeNode::Leaf { .. } => None,
+        };
+
+        leaf_paths(split_of)
+            .into_iter()
+            .map(|(leaf, conditions)| {
+                let node = &self.nodes[leaf];
+                let samples = match node {
+                    TreeNode::Split { samples, .. } | TreeNode::Leaf { samples, .. } => *samples,
+                };
+                let outcome = format!(
+                    "{} ({:.0}% pass likelihood, {} training students)",
+                    if node.probability() >= 0.5 { "Pass" } else { "Fail" },
+                    node.probability() * 100.0,
+                    samples
+                );
+                format_rule(&conditions, &outcome)
+            })
+            .collect()
+    }
+
+    // Indices of the nodes visited from the root down to the student's leaf
+    fn path(&self, vector: &[f64]) -> Vec<usize> {
+        let mut path = vec![0];
+        let mut index = 0;
+        while let TreeNode::Split { feature, threshold, left, right, .. } = &self.nodes[index] {
+            index = if vector[*feature] <= *threshold { *left } else { *right };
+            path.push(index);
+        }
+        path
+    }
+}
+
+impl Predictor for DecisionTree {
+    fn predict_proba(&self, features: &StudentFeatures) -> f64 {
+        let path = self.path(&features.to_vector());
+        self.nodes[*path.last().unwrap_or(&0)].probability()
+    }
+
+    // Follows the decision path and credits each split's change in log-odds
+    // to the feature it tested, so the contributions add up to the leaf's
+    // log-odds together with the root's
+    fn explain(&self, features: &StudentFeatures) -> PredictionExplanation {
+        let vector = features.to_vector();
+        let imputed = imputed_flags(features);
+        let mut totals = vec![0.0; vector.len()];
+
+        let path = self.path(&vector);
+        for pair in path.windows(2) {
+            if let TreeNode::Split { feature, .. } = &self.nodes[pair[0]] {
+                totals[*feature] += logit(self.nodes[pair[1]].probability()) - logit(self.nodes[pair[0]].probability());
+            }
+        }
+
+        let mut contributions: Vec<FeatureContribution> = (0..vector.len())
+            .map(|j| FeatureContribution {
+                feature: FEATURE_NAMES[j].to_string(),
+                value: vector[j],
+                contribution: totals[j],
+                imputed: imputed[j],
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+        PredictionExplanation {
+            baseline_log_odds: logit(self.nodes[0].probability()),
+            contributions,
+        }
+    }
+
+    fn metadata(&self) -> ModelInfo {
+        family_info(ModelFamily::DecisionTree, self.training_size, self.training_accuracy, &self.trained_at)
+    }
+}
+
+// Walks a tree stored as an index-linked node vector rooted at 0. `split_of`
+// returns (feature, threshold, left, right) for split nodes and None for
+// leaves. Yields every leaf with the conditions on the path to it
+pub(crate) fn leaf_paths(split_of: impl Fn(usize) -> Option<(usize, f64, usize, usize)>) -> Vec<(usize, Vec<String>)> {
+    let mut paths = Vec::new();
+    let mut stack = vec![(0, Vec::new())];
+
+    while let Some((index, conditions)) = stack.pop() {
+        match split_of(index) {
+            Some((feature, threshold, left, right)) => {
+                let mut right_conditions = conditions.clone();
+                right_conditions.push(format!("{} > {:.2}", FEATURE_NAMES[feature], threshold));
+                let mut left_conditions = conditions;
+                left_conditions.push(format!("{} <= {:.2}", FEATURE_NAMES[feature], threshold));
+
+                // Pushed right first so the left branch is reported first
+                stack.push((right, right_conditions));
+                stack.push((left, left_conditions));
+            }
+            None => paths.push((index, conditions)),
+        }
+    }
+
+    paths
+}
+
+pub(crate) fn format_rule(conditions: &[String], outcome: &str) -> String {
+    if conditions.is_empty() {
+        format!("ALWAYS {}", outcome)
+    } else {
+        format!("IF {} THEN {}", conditions.join(" AND "), outcome)
+    }
+}
+
+// Laplace-smoothed pass rate so leaves never claim certainty
+fn smoothed_pass_rate(labels: &[bool], indices: &[usize]) -> f64 {
+    let passes = indices.iter().filter(|&&i| labels[i]).count();
+    (passes as f64 + 1.0) / (indices.len() as f64 + 2.0)
+}
+
+fn gini(passes: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let p = passes as f64 / total as f64;
+    2.0 * p * (1.0 - p)
+}
+
+// Grows the subtree for `indices` and returns the index of its root
+fn grow(nodes: &mut Vec<TreeNode>, rows: &[Vec<f64>], labels: &[bool], indices: Vec<usize>, depth: usize, config: &TrainingConfig) -> usize {
+    let index = nodes.len();
+    let probability = smoothed_pass_rate(labels, &indices);
+    let samples = indices.len();
+    nodes.push(TreeNode::Leaf { probability, samples });
+
+    if depth >= config.max_depth {
+        return index;
+    }
+
+    let Some((feature, threshold)) = best_split(rows, labels, &indices, config.min_samples_leaf.max(1)) else {
+        return index;
+    };
+
+    let (left_indices, right_indices): (Vec<usize>, Vec<usize>) =
+        indices.into_iter().partition(|&i| rows[i][feature] <= threshold);
+    let left = grow(nodes, rows, labels, left_indices, depth + 1, config);
+    let right = grow(nodes, rows, labels, right_indices, depth + 1, config);
+
+    nodes[index] = TreeNode::Split { feature, threshold, left, right, probability, samples };
+    index
+}
+
+// The (feature, threshold) with the lowest weighted Gini impurity, if any
+// split improves on the node itself and leaves `min_leaf` students per side
+fn best_split(rows: &[Vec<f64>], labels: &[bool], indices: &[usize], min_leaf: usize) -> Option<(usize, f64)> {
+    let total = indices.len();
+    let total_passes = indices.iter().filter(|&&i| labels[i]).count();
+    let mut best: Option<(f64, usize, f64)> = None;
+    let mut best_impurity = gini(total_passes, total);
+
+    for (feature, _) in FEATURE_NAMES.iter().enumerate() {
+        let mut sorted = indices.to_vec();
+        sorted.sort_by(|&a, &b| rows[a][feature].total_cmp(&rows[b][feature]));
+
+        let mut left_passes = 0;
+        for split in 1..total {
+            if labels[sorted[split - 1]] {
+                left_passes += 1;
+            }
+
+            let lower = rows[sorted[split - 1]][feature];
+            let upper = rows[sorted[split]][feature];
+            if lower == upper || split < min_leaf || total - split < min_leaf {
+                continue;
+            }
+
+            let impurity = (split as f64 * gini(left_passes, split)
+                + (total - split) as f64 * gini(total_passes - left_passes, total - split))
+                / total as f64;
+            if impurity < best_impurity - 1e-12 {
+                best_impurity = impurity;
+                best = Some((impurity, feature, (lower + upper) / 2.0));
+            }
+        }
+    }
+
+    best.map(|(_, feature, threshold)| (feature, threshold))
+}
@@ -1,12 +1,861 @@
+use student_classifier::calibration::{CalibrationMethod, Calibrator};
+use student_classifier::counterfactual::{find_counterfactual, FeatureBounds};
+use student_classifier::data::{load_dataset, parse_dataset, stratified_k_fold, DEFAULT_DATA_PATH};
+use student_classifier::analytics::{student_histories, TrendsAnalyzer};
+use student_classifier::database::{Database, ShadowPrediction, ANONYMOUS_STUDENT, StudentOutcome, TrendFilter, WeeklyRecord};
+use student_classifier::drift::{feature_drift, ks_statistic};
+use student_classifier::fairness::fairness_audit;
+use student_classifier::evaluation::{evaluate_probabilities, roc_auc};
+use student_classifier::grades::probability_at_least;
+use student_classifier::forecast::{forecast_risk, validate_forecast, LinearTrend, WeeklyObservation, MAX_TERM_WEEKS};
+use student_classifier::incremental::{cap_drift, IncrementalOptions};
+use student_classifier::peers::{PeerIndex, PeerSource};
+use student_classifier::preprocessing::{ImputationStrategy, PreprocessingConfig, Preprocessor, ScalingMethod};
+use student_classifier::registry::ModelRegistry;
+use student_classifier::retrain::{outcome_records, retrain_candidate, RetrainOptions};
+use student_classifier::shadow::shadow_report;
+use student_classifier::search::{hyperparameter_search, model_configs, SearchOptions, SearchSpace};
+use student_classifier::threshold::{tune_threshold, CostMatrix, ThresholdObjective};
+use student_classifier::model::{load_or_train_model, train_model, train_model_with_config, train_validated_model, StudentFeatures, StudentRecord, StudyPlanRequest, TrainedModel, TrainingConfig, UncertaintyOptions};
+use student_classifier::predictor::{ModelFamily, Predictor};
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Grade labels only exist in this fixture; data/students.csv has none
+    const GRADED_CSV: &str = "hours,attendance,previous_grades,sleep_hours,pass,grade
+5.0,80.0,75.0,7.0,1.0,C
+2.0,60.0,45.0,5.0,0.0,Fail
+6.0,90.0,85.0,8.0,1.0,B
+3.0,70.0,55.0,6.0,0.0,Fail
+7.0,85.0,88.0,7.5,1.0,A
+1.0,50.0,35.0,4.0,0.0,Fail
+8.0,95.0,92.0,8.0,1.0,A
+4.0,65.0,60.0,6.5,0.0,Fail
+9.0,92.0,90.0,7.0,1.0,A
+3.5,75.0,65.0,7.0,1.0,Pass
+6.5,88.0,82.0,7.5,1.0,B
+2.5,55.0,48.0,5.5,0.0,Fail";
+
     #[test]
     fn test_accuracy_calculation() {
-        let predictions = vec![true, true, false, true];
-        let targets = vec![true, false, false, true];
+        let predictions = [true, true, false, true];
+        let targets = [true, false, false, true];
         
         assert_eq!(predictions.len(), targets.len());
+
+        let probabilities: Vec<f64> = predictions.iter().map(|&p| if p { 0.9 } else { 0.1 }).collect();
+        let report = evaluate_probabilities(&probabilities, &targets, 0.5);
+        assert_eq!(report.accuracy, 0.75);
+        assert_eq!(report.confusion_matrix.false_positives, 1);
+        assert!((report.precision - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(report.recall, 1.0);
+    }
+
+    #[test]
+    fn test_roc_auc_ranks_passes_above_fails() {
+        let labels = [false, false, true, true];
+        assert_eq!(roc_auc(&[0.1, 0.4, 0.35, 0.8], &labels), 0.75);
+        assert_eq!(roc_auc(&[0.1, 0.2, 0.7, 0.8], &labels), 1.0);
+    }
+
+    #[test]
+    fn test_model_trains_on_dataset() {
+        let records = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let info = model.metadata();
+
+        assert_eq!(info.training_data_size, records.len());
+        assert!(info.accuracy > 0.8);
+
+        let (strong, _) = model.predict(&StudentFeatures::new(8.0, 95.0));
+        let (weak, _) = model.predict(&StudentFeatures::new(1.0, 50.0));
+        assert!(strong);
+        assert!(!weak);
+    }
+
+    #[test]
+    fn test_training_rejects_empty_dataset() {
+        assert!(TrainedModel::fit(&[], &TrainingConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_predictions_are_deterministic() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let features = StudentFeatures::new(4.5, 72.0);
+
+        assert_eq!(model.predict(&features), model.predict(&features));
+
+        let options = UncertaintyOptions { seed: 42, samples: 50 };
+        let first = model.predict_with_uncertainty(&features, &options);
+        let second = model.predict_with_uncertainty(&features, &options);
+        assert_eq!(first.mean_probability, second.mean_probability);
+        assert_eq!(first.std_dev, second.std_dev);
+
+        // /predict answers 400 to sample counts it would not draw
+        assert!(options.validate().is_ok());
+        assert!(UncertaintyOptions { seed: 42, samples: 1_000_000_000_000 }.validate().is_err());
+        assert!(UncertaintyOptions { seed: 42, samples: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_optional_features_affect_prediction() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let mut strong_history = StudentFeatures::new(4.0, 70.0);
+        strong_history.previous_grades = Some(90.0);
+        let mut weak_history = StudentFeatures::new(4.0, 70.0);
+        weak_history.previous_grades = Some(40.0);
+
+        assert!(model.predict_proba(&strong_history) > model.predict_proba(&weak_history));
+    }
+
+    #[test]
+    fn test_model_artifact_round_trip() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let path = std::env::temp_dir().join("student_classifier_model_test.json");
+        let path = path.to_str().unwrap();
+
+        model.save(path).unwrap();
+        let loaded = TrainedModel::load(path).unwrap();
+
+        let features = StudentFeatures::new(5.0, 78.0);
+        assert_eq!(model.predict_proba(&features), loaded.predict_proba(&features));
+        assert_eq!(loaded.training().training_size, model.training().training_size);
+        assert_eq!(loaded.metadata().last_updated, model.training().trained_at);
+
+        // An artifact from an older format is retrained rather than fatal
+        let old = model.to_json().unwrap().replacen("\"format_version\": 2", "\"format_version\": 1", 1);
+        std::fs::write(path, old).unwrap();
+        assert!(TrainedModel::load(path).is_err());
+        load_or_train_model(DEFAULT_DATA_PATH, Some(path), &TrainingConfig::default()).unwrap();
+        assert!(TrainedModel::load(path).is_ok());
+        let backup = format!("{}.v1.bak", path);
+        assert!(TrainedModel::load(&backup).is_err());
+        std::fs::remove_file(&backup).unwrap();
+
+        // A corrupt artifact is an error and is left in place
+        std::fs::write(path, "{\"format_version\": 2, \"estim").unwrap();
+        assert!(load_or_train_model(DEFAULT_DATA_PATH, Some(path), &TrainingConfig::default()).is_err());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "{\"format_version\": 2, \"estim");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registry_activation_and_rollback() {
+        let db = Database::new().await.unwrap();
+        let registry = ModelRegistry::bootstrap(&db, train_model(DEFAULT_DATA_PATH).unwrap()).await.unwrap();
+        assert_eq!(registry.active_version(), "1.0.0");
+
+        let candidate = registry.register(&db, &train_model(DEFAULT_DATA_PATH).unwrap()).await.unwrap();
+        assert!(!candidate.is_active);
+
+        registry.activate(&db, &candidate.version).await.unwrap();
+        assert_eq!(registry.active_version(), candidate.version);
+
+        let restored = registry.rollback(&db).await.unwrap();
+        assert_eq!(restored.version, "1.0.0");
+        assert_eq!(registry.active_version(), "1.0.0");
+        assert!(registry.activate(&db, "9.9.9").await.is_err());
+
+        // Concurrent registrations get distinct numbers
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let (second, third) = tokio::join!(registry.register(&db, &model), registry.register(&db, &model));
+        let (second, third) = (second.unwrap(), third.unwrap());
+        assert_ne!(second.version, third.version);
+
+        // Repeated rollbacks walk back through the activations
+        registry.activate(&db, &candidate.version).await.unwrap();
+        registry.activate(&db, &second.version).await.unwrap();
+        registry.activate(&db, &third.version).await.unwrap();
+        assert_eq!(registry.rollback(&db).await.unwrap().version, second.version);
+        assert_eq!(registry.rollback(&db).await.unwrap().version, candidate.version);
+        assert_eq!(registry.rollback(&db).await.unwrap().version, "1.0.0");
+        assert!(registry.rollback(&db).await.is_err());
+    }
+
+    #[test]
+    fn test_stratified_folds_cover_dataset() {
+        let records = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let folds = stratified_k_fold(&records, 4, 7);
+
+        assert_eq!(folds.len(), 4);
+        assert_eq!(folds.iter().map(|(_, test)| test.len()).sum::<usize>(), records.len());
+        for (train, test) in &folds {
+            assert_eq!(train.len() + test.len(), records.len());
+            assert!(test.iter().any(|r| r.passed()));
+            assert!(test.iter().any(|r| !r.passed()));
+        }
+    }
+
+    #[test]
+    fn test_explanation_adds_up_to_prediction() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let features = StudentFeatures::new(3.0, 60.0);
+        let explanation = model.explain(&features);
+
+        let log_odds = explanation.baseline_log_odds
+            + explanation.contributions.iter().map(|c| c.contribution).sum::<f64>();
+        let probability = 1.0 / (1.0 + (-log_odds).exp());
+        assert!((probability - model.predict_proba(&features)).abs() < 1e-9);
+        assert_eq!(explanation.contributions.iter().filter(|c| c.imputed).count(), 2);
+    }
+
+    #[test]
+    fn test_counterfactual_reaches_pass_within_bounds() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let features = StudentFeatures::new(2.0, 55.0);
+        assert!(!model.predict(&features).0);
+
+        let result = find_counterfactual(&model, &features, 0.5, &FeatureBounds::default());
+        assert!(result.achievable);
+        assert!(result.resulting_probability >= 0.5);
+        assert!(result.required_attendance <= 100.0);
+        assert!(result.hours_change >= 0.0 && result.attendance_change >= 0.0);
+
+        let huge = FeatureBounds { max_hours: 1e9, ..FeatureBounds::default() };
+        assert!(huge.validate(&features).is_err());
+        let nan = FeatureBounds { max_attendance: f64::NAN, ..FeatureBounds::default() };
+        assert!(nan.validate(&features).is_err());
+        assert!(FeatureBounds::default().validate(&StudentFeatures::new(-1e9, 55.0)).is_err());
+        assert!(FeatureBounds::default().validate(&features).is_ok());
+    }
+
+    #[test]
+    fn test_grade_distribution() {
+        assert!(train_model(DEFAULT_DATA_PATH).unwrap().grade_distribution(&StudentFeatures::new(8.5, 94.0)).is_none());
+
+        let records = parse_dataset(GRADED_CSV).unwrap();
+        let model = train_validated_model(&records, &TrainingConfig::default()).unwrap();
+        let distribution = model.grade_distribution(&StudentFeatures::new(8.5, 94.0)).unwrap();
+
+        assert_eq!(distribution.len(), 5);
+        assert!((distribution.iter().map(|g| g.probability).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(model.predicted_grade(&StudentFeatures::new(8.5, 94.0)).unwrap(), "A");
+        assert_eq!(model.predicted_grade(&StudentFeatures::new(1.5, 52.0)).unwrap(), "Fail");
+
+        // Grades agree with the Pass/Fail call everywhere, including borderline students
+        for hours in [1.0, 2.5, 3.0, 3.5, 4.0, 4.5, 6.0] {
+            let features = StudentFeatures::new(hours, 68.0);
+            let distribution = model.grade_distribution(&features).unwrap();
+            let at_least_pass = probability_at_least(&distribution, "Pass").unwrap();
+            assert!((at_least_pass - model.predict_proba(&features)).abs() < 1e-9);
+            assert_eq!(model.predicted_grade(&features).unwrap() == "Fail", !model.predict(&features).0);
+        }
+
+        // A "Pass" study plan reaches the model's own cutoff, not 0.5
+        let strict = model.with_threshold(&records, ThresholdObjective::Fixed { threshold: 0.7 }).unwrap();
+        let plan = strict.generate_study_plan(&StudyPlanRequest {
+            student_name: "Test".to_string(),
+            current_hours: 2.0,
+            current_attendance: 60.0,
+            target_grade: "Pass".to_string(),
+            available_days: vec!["Monday".to_string()],
+            preferred_times: vec![],
+            seed: None,
+        });
+        let recommended = StudentFeatures::new(plan.recommended_hours, plan.target_attendance);
+        assert!(strict.predict_proba(&recommended) >= 0.7);
+        assert!(strict.predict(&recommended).0);
+    }
+
+    #[test]
+    fn test_model_families_are_selectable_by_config() {
+        for (family, model_type) in [
+            (ModelFamily::LogisticRegression, "Logistic Regression"),
+            (ModelFamily::DecisionTree, "Decision Tree"),
+            (ModelFamily::GradientBoosting, "Gradient-Boosted Trees"),
+            (ModelFamily::KNearestNeighbours, "K-Nearest Neighbours"),
+        ] {
+            let config = TrainingConfig { family, ..TrainingConfig::default() };
+            let model = train_model_with_config(DEFAULT_DATA_PATH, &config).unwrap();
+            assert_eq!(model.metadata().model_type, model_type);
+
+            let strong = StudentFeatures::new(8.5, 95.0);
+            let weak = StudentFeatures::new(1.0, 50.0);
+            assert!(model.predict(&strong).0, "{:?}", family);
+            assert!(!model.predict(&weak).0, "{:?}", family);
+            assert_eq!(model.explain(&weak).contributions.len(), 4);
+
+            let loaded = TrainedModel::from_json(&model.to_json().unwrap()).unwrap();
+            assert_eq!(loaded.family(), family);
+            assert_eq!(loaded.predict_proba(&weak), model.predict_proba(&weak));
+        }
+    }
+
+    #[test]
+    fn test_tree_models_export_rules() {
+        let tree_config = TrainingConfig { family: ModelFamily::DecisionTree, ..TrainingConfig::default() };
+        let tree = train_model_with_config(DEFAULT_DATA_PATH, &tree_config).unwrap();
+        let rules = tree.rules().unwrap();
+        assert!(!rules.is_empty());
+        assert!(rules.iter().all(|r| r.starts_with("IF ") || r.starts_with("ALWAYS ")));
+
+        let boosted_config = TrainingConfig { family: ModelFamily::GradientBoosting, ..TrainingConfig::default() };
+        let boosted = train_model_with_config(DEFAULT_DATA_PATH, &boosted_config).unwrap();
+        assert!(boosted.rules().unwrap()[0].starts_with("START"));
+
+        // Decision-path contributions add up to the prediction's log-odds
+        let features = StudentFeatures::new(4.5, 72.0);
+        let explanation = boosted.explain(&features);
+        let total = explanation.baseline_log_odds + explanation.contributions.iter().map(|c| c.contribution).sum::<f64>();
+        let p = boosted.predict_proba(&features);
+        assert!((total - (p / (1.0 - p)).ln()).abs() < 1e-9);
+
+        assert!(train_model(DEFAULT_DATA_PATH).unwrap().rules().is_none());
+    }
+
+    #[test]
+    fn test_calibrators_preserve_ranking() {
+        let scores = [0.1, 0.3, 0.35, 0.6, 0.7, 0.9];
+        let labels = [false, true, false, false, true, true];
+
+        for method in [CalibrationMethod::Platt, CalibrationMethod::Isotonic] {
+            let calibrator = Calibrator::fit(method, &scores, &labels).unwrap();
+            let calibrated: Vec<f64> = scores.iter().map(|&s| calibrator.apply(s)).collect();
+            assert!(calibrated.windows(2).all(|w| w[0] <= w[1]), "{:?}", method);
+            assert!(calibrated.iter().all(|p| (0.0..=1.0).contains(p)));
+            // The smallest score reaching a probability
+            let cutoff = calibrator.inverse(0.5);
+            assert!(calibrator.apply(cutoff) >= 0.5 - 1e-9 && calibrator.apply(cutoff - 1e-6) < 0.5, "{:?}", method);
+        }
+
+        // 0.3 (pass), 0.35 and 0.6 (fail) violate monotonicity and are pooled
+        let isotonic = Calibrator::fit(CalibrationMethod::Isotonic, &scores, &labels).unwrap();
+        assert!((isotonic.apply(1.25 / 3.0) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(isotonic.apply(0.05), 0.0);
+        assert_eq!(isotonic.apply(0.95), 1.0);
+        assert!(Calibrator::fit(CalibrationMethod::Platt, &scores, &[true; 6]).is_none());
+
+        let info = train_model(DEFAULT_DATA_PATH).unwrap().metadata();
+        assert_eq!(info.calibration.as_deref(), Some("Platt scaling"));
+        assert!(info.calibration_error.is_some());
+    }
+
+    #[test]
+    fn test_review_flags_unusual_and_borderline_students() {
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+
+        let typical = model.review(&StudentFeatures::new(8.5, 95.0));
+        let interval = typical.interval.unwrap();
+        assert!(interval.lower <= interval.upper);
+        assert_eq!(interval.models, TrainingConfig::default().bootstrap_models);
+        assert!(!typical.out_of_distribution);
+
+        let unusual = model.review(&StudentFeatures::new(60.0, 95.0));
+        assert!(unusual.out_of_distribution && unusual.needs_review);
+        assert_eq!(unusual.unusual_features, vec!["study_hours".to_string()]);
+
+        let batch = model.batch_predict(
+            vec![StudentRecord { name: "Outlier".to_string(), features: StudentFeatures::new(60.0, 95.0) }],
+            false,
+        );
+        assert_eq!(batch.summary.review_count, 1);
+    }
+
+    #[test]
+    fn test_threshold_tuning_favours_catching_at_risk_students() {
+        let probabilities = [0.2, 0.45, 0.55, 0.6, 0.8, 0.9];
+        let labels = [false, false, false, true, true, true];
+
+        let recall = tune_threshold(&probabilities, &labels, ThresholdObjective::TargetRecall { recall: 1.0 });
+        assert!((recall.threshold - 0.575).abs() < 1e-9);
+        assert_eq!(recall.at_risk_recall, 1.0);
+        assert_eq!(recall.false_alarm_rate, 0.0);
+
+        // A missed at-risk student costing 10 false alarms pushes the cutoff up
+        let costs = CostMatrix { missed_at_risk: 10.0, false_alarm: 1.0 };
+        let noisy_labels = [false, false, true, false, true, true];
+        let costly = tune_threshold(&probabilities, &noisy_labels, ThresholdObjective::MinimizeCost { costs });
+        assert!(costly.threshold > 0.55);
+        assert_eq!(costly.cost, 1.0);
+
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let records = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let tuned = model.with_threshold(&records, ThresholdObjective::Fixed { threshold: 0.7 }).unwrap();
+        assert_eq!(tuned.metadata().decision_threshold, 0.7);
+        assert_eq!(tuned.decision_threshold(), 0.7);
+        assert_eq!(model.metadata().decision_threshold, 0.5);
+        assert!(model.with_threshold(&records, ThresholdObjective::Fixed { threshold: 1.5 }).is_err());
+        assert!(model.with_threshold(&records, ThresholdObjective::TargetRecall { recall: f64::NAN }).is_err());
+        let negative = CostMatrix { missed_at_risk: -1.0, false_alarm: 1.0 };
+        assert!(model.with_threshold(&records, ThresholdObjective::MinimizeCost { costs: negative }).is_err());
+    }
+
+    #[test]
+    fn test_hyperparameter_search_ranks_configurations() {
+        let records = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let space = SearchSpace {
+            families: vec![ModelFamily::DecisionTree, ModelFamily::KNearestNeighbours],
+            max_depths: vec![1, 3],
+            min_samples_leaf: vec![1],
+            k_neighbours: vec![1, 3],
+            thresholds: vec![0.4, 0.6],
+            ..SearchSpace::default()
+        };
+        assert_eq!(model_configs(&space, &TrainingConfig::default()).len(), 4);
+
+        let leaderboard = hyperparameter_search(&records, &TrainingConfig::default(), &space, &SearchOptions::default()).unwrap();
+        assert_eq!(leaderboard.len(), 8);
+        assert_eq!(leaderboard[0].rank, 1);
+        assert!(leaderboard.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let options = SearchOptions { trials: Some(1), ..SearchOptions::default() };
+        assert_eq!(hyperparameter_search(&records, &TrainingConfig::default(), &space, &options).unwrap().len(), 2);
+
+        // Client-supplied spaces are bounded before anything is refitted
+        let deep = SearchSpace { max_depths: vec![1_000_000], ..space.clone() };
+        assert!(hyperparameter_search(&records, &TrainingConfig::default(), &deep, &options).is_err());
+        let cutoffs = SearchSpace { thresholds: vec![f64::NAN], ..space.clone() };
+        assert!(hyperparameter_search(&records, &TrainingConfig::default(), &cutoffs, &options).is_err());
+        let folds = SearchOptions { folds: 10_000, ..options };
+        assert!(hyperparameter_search(&records, &TrainingConfig::default(), &space, &folds).is_err());
+        let wide = SearchSpace { learning_rates: vec![0.1; 10], l2_penalties: vec![0.0; 10], ..SearchSpace::default() };
+        assert!(hyperparameter_search(&records, &TrainingConfig::default(), &wide, &SearchOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_fairness_audit_breaks_down_by_group() {
+        let csv = "student_id,hours,attendance,previous_grades,sleep_hours,pass,cohort,campus
+s1,8.0,95.0,85.0,8.0,1.0,2023,main
+s2,7.0,90.0,80.0,7.0,1.0,2023,
+s3,2.0,55.0,45.0,5.0,0.0,2023,main
+s4,8.0,92.0,82.0,8.0,1.0,2024,city
+s5,3.0,60.0,50.0,6.0,1.0,2024,city
+s6,1.5,50.0,40.0,5.0,0.0,2024,city";
+        let records = parse_dataset(csv).unwrap();
+        assert_eq!(records[0].groups.get("cohort").map(String::as_str), Some("2023"));
+        assert!(!records[1].groups.contains_key("campus"));
+
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        // Only declared attributes are grouped on, never the id column
+        let declared = ["cohort".to_string(), "campus".to_string()];
+        let report = fairness_audit(&model, &records, &declared).unwrap();
+        let attributes: Vec<&str> = report.attributes.iter().map(|a| a.attribute.as_str()).collect();
+        assert_eq!(attributes, ["campus", "cohort"]);
+        assert!(fairness_audit(&model, &records, &[]).is_err());
+        assert!(fairness_audit(&model, &records, &["hours".to_string()]).is_err());
+
+        let cohort = &report.attributes[1];
+        assert_eq!(cohort.groups.len(), 2);
+        assert_eq!(cohort.groups.iter().map(|g| g.count).sum::<usize>(), 6);
+        // The 2024 student who passed on 3 hours is missed, the 2023 passes are not
+        assert_eq!(cohort.groups[0].false_negative_rate, 0.0);
+        assert!(cohort.groups[1].false_negative_rate > 0.0);
+        assert!((cohort.equal_opportunity_difference - cohort.groups[1].false_negative_rate).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drift_statistics_flag_shifted_features() {
+        let reference: Vec<f64> = (0..40).map(|i| 60.0 + i as f64).collect();
+        let same: Vec<f64> = (0..40).map(|i| 60.5 + i as f64).collect();
+        let shifted: Vec<f64> = (0..40).map(|i| 30.0 + i as f64 / 2.0).collect();
+
+        assert_eq!(ks_statistic(&reference, &reference), 0.0);
+        assert_eq!(ks_statistic(&[1.0, 2.0], &[3.0, 4.0]), 1.0);
+
+        let stable = feature_drift("attendance", &reference, &same).unwrap();
+        assert!(!stable.drifted);
+        assert!(stable.psi < 0.1);
+
+        let drifted = feature_drift("attendance", &reference, &shifted).unwrap();
+        assert!(drifted.drifted);
+        assert!(drifted.psi > 0.25 && drifted.ks_statistic > drifted.ks_critical_value);
+        assert!(feature_drift("sleep_hours", &reference, &[]).is_none());
+        // A handful of values is compared but not flagged
+        assert!(!feature_drift("attendance", &reference, &shifted[..5]).unwrap().drifted);
+    }
+
+    #[tokio::test]
+    async fn test_drift_monitor_uses_recent_predictions() {
+        let db = Database::new().await.unwrap();
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        for i in 0..45 {
+            let features = StudentFeatures::new(25.0 + (i % 5) as f64 * 3.0, 99.0);
+            let record = student_classifier::database::StudentRecord {
+                id: 0,
+                name: "Drifted".to_string(),
+                study_hours: features.hours,
+                attendance: features.attendance,
+                previous_grades: None,
+                sleep_hours: None,
+                predicted_pass: model.predict(&features).0,
+                confidence: model.predict_proba(&features),
+                predicted_grade: None,
+                model_version: None,
+                created_at: chrono::Utc::now(),
+            };
+            db.save_prediction(&record).await.unwrap();
+        }
+
+        let reference = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let predictions = db.get_recent_predictions(4).await.unwrap();
+        assert_eq!(predictions.len(), 4);
+        // Too few predictions to flag anything
+        let report = student_classifier::drift::monitor_drift(&reference, &predictions, 2);
+        assert_eq!(report.windows.len(), 2);
+        assert!(!report.retraining_recommended);
+
+        let predictions = db.get_recent_predictions(45).await.unwrap();
+        let report = student_classifier::drift::monitor_drift(&reference, &predictions, 20);
+        // The 5 oldest predictions do not fill a window and are left out
+        assert_eq!(report.windows.len(), 2);
+        assert!(report.windows.iter().all(|w| w.sample_size == 20));
+        // Only supplied features are compared
+        assert_eq!(report.windows[0].features.len(), 2);
+        assert!(report.retraining_recommended);
+        assert!(report.drifted_features.contains(&"study_hours".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_outcomes_feed_retraining() {
+        let db = Database::new().await.unwrap();
+        let stored = |name: &str, hours: f64, attendance: f64| student_classifier::database::StudentRecord {
+            id: 0,
+            name: name.to_string(),
+            study_hours: hours,
+            attendance,
+            previous_grades: None,
+            sleep_hours: Some(8.0),
+            predicted_pass: true,
+            confidence: 0.8,
+            predicted_grade: None,
+            model_version: None,
+            created_at: chrono::Utc::now(),
+        };
+        db.save_prediction(&stored("Ada", 2.0, 60.0)).await.unwrap();
+        db.save_prediction(&stored("Ada", 9.0, 95.0)).await.unwrap();
+        db.save_prediction(&stored("Bo", 1.0, 40.0)).await.unwrap();
+
+        let outcome = |name: &str, passed: bool| StudentOutcome {
+            name: name.to_string(),
+            passed,
+            grade: None,
+            recorded_at: chrono::Utc::now(),
+        };
+        assert_eq!(db.save_outcome(&outcome("Ada", true)).await.unwrap(), 2);
+        assert_eq!(db.save_outcome(&outcome("Bo", true)).await.unwrap(), 1);
+        // Recording again replaces the label; unknown students are ignored
+        assert_eq!(db.save_outcome(&outcome("Bo", false)).await.unwrap(), 1);
+        assert_eq!(db.save_outcome(&outcome("Nobody", true)).await.unwrap(), 0);
+        // Anonymous predictions are never labelled
+        db.save_prediction(&stored(ANONYMOUS_STUDENT, 3.0, 70.0)).await.unwrap();
+        assert_eq!(db.save_outcome(&outcome(ANONYMOUS_STUDENT, true)).await.unwrap(), 0);
+
+        let labelled = db.get_labelled_predictions().await.unwrap();
+        assert_eq!(labelled.len(), 2);
+        let active = train_model(DEFAULT_DATA_PATH).unwrap();
+        let records = outcome_records(&labelled, &active);
+        // Ada's most recent prediction is used, with the model's imputation for missing features
+        assert_eq!((records[0].hours, records[0].previous_grades, records[0].sleep_hours), (9.0, 70.0, 8.0));
+        assert!(records[0].passed() && !records[1].passed());
+
+        let base = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let mean_config = TrainingConfig {
+            preprocessing: PreprocessingConfig { imputation: ImputationStrategy::Mean, ..PreprocessingConfig::default() },
+            ..TrainingConfig::default()
+        };
+        let mean_imputed = train_model_with_config(DEFAULT_DATA_PATH, &mean_config).unwrap();
+        let mean_grades = base.iter().map(|r| r.previous_grades).sum::<f64>() / base.len() as f64;
+        assert!((outcome_records(&labelled, &mean_imputed)[0].previous_grades - mean_grades).abs() < 1e-9);
+        let until = labelled.iter().map(|(_, outcome)| outcome.recorded_at).max();
+        let (candidate, report) = retrain_candidate(&base, &records, until, &active, &active.training().config, &RetrainOptions::default()).unwrap();
+        assert_eq!(report.outcome_records, 2);
+        assert_eq!(candidate.training().outcome_records, 2);
+        // Scored on the split, but registered trained on every merged record
+        assert_eq!(candidate.training().training_size, base.len() + 2);
+        assert_eq!(candidate.training().outcomes_until, until);
+        // Incremental steps start after the outcomes it was retrained on
+        assert_eq!(candidate.outcomes_learned_until(), until);
+        assert!(active.outcomes_learned_until().is_none());
+        assert_eq!(report.train_size + report.holdout_size, base.len() + 2);
+        assert_eq!(report.candidate.sample_size, report.holdout_size);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_challenger_comparison() {
+        let db = Database::new().await.unwrap();
+        let registry = ModelRegistry::bootstrap(&db, train_model(DEFAULT_DATA_PATH).unwrap()).await.unwrap();
+        let tree = TrainingConfig { family: ModelFamily::DecisionTree, ..TrainingConfig::default() };
+        let challenger = registry.register(&db, &train_model_with_config(DEFAULT_DATA_PATH, &tree).unwrap()).await.unwrap();
+
+        assert!(registry.set_challenger(&db, "1.0.0").await.is_err());
+        registry.set_challenger(&db, &challenger.version).await.unwrap();
+        let (version, shadow_model) = registry.challenger().unwrap();
+        assert_eq!(version, challenger.version);
+
+        let champion = registry.current();
+        for (name, hours, attendance) in [("Ada", 9.0, 95.0), ("Bo", 1.0, 40.0), ("Cy", 5.0, 75.0)] {
+            let features = StudentFeatures::new(hours, attendance);
+            let record = student_classifier::database::StudentRecord {
+                id: 0,
+                name: name.to_string(),
+                study_hours: hours,
+                attendance,
+                previous_grades: None,
+                sleep_hours: None,
+                predicted_pass: champion.predict(&features).0,
+                confidence: champion.predict_proba(&features),
+                predicted_grade: None,
+                model_version: Some(registry.active_version()),
+                created_at: chrono::Utc::now(),
+            };
+            let prediction_id = db.save_prediction(&record).await.unwrap();
+            let shadow = ShadowPrediction {
+                id: 0,
+                prediction_id,
+                model_version: version.clone(),
+                predicted_pass: shadow_model.predict(&features).0,
+                confidence: shadow_model.predict_proba(&features),
+                created_at: chrono::Utc::now(),
+            };
+            db.save_shadow_prediction(&shadow).await.unwrap();
+        }
+        for (name, passed) in [("Ada", true), ("Bo", false)] {
+            let outcome = StudentOutcome { name: name.to_string(), passed, grade: None, recorded_at: chrono::Utc::now() };
+            db.save_outcome(&outcome).await.unwrap();
+        }
+
+        let comparisons = db.get_shadow_comparisons(&version).await.unwrap();
+        let report = shadow_report(&version, &comparisons);
+        assert_eq!(report.compared, 3);
+        assert_eq!(report.labelled, 2);
+        assert_eq!(report.champion_versions, vec!["1.0.0".to_string()]);
+        assert!((0.0..=1.0).contains(&report.agreement_rate));
+        assert!(report.champion_accuracy.is_some() && report.challenger_accuracy.is_some());
+
+        // Promoting the challenger ends the shadow run
+        registry.activate(&db, &version).await.unwrap();
+        assert!(registry.challenger().is_none());
+        assert!(registry.clear_challenger().is_none());
+    }
+
+    #[test]
+    fn test_similar_students_lookup() {
+        let dataset = parse_dataset("hours,attendance,previous_grades,sleep_hours,pass\n\
+            2,50,40,6,0\n\
+            8,90,85,8,1\n\
+            7,88,60,7,1\n\
+            3,55,80,6,0\n").unwrap();
+        let outcome = parse_dataset("hours,attendance,previous_grades,sleep_hours,pass\n8,91,84,8,0\n").unwrap();
+        let index = PeerIndex::build(&dataset, &outcome);
+        assert_eq!(index.len(), 5);
+
+        let mut student = StudentFeatures::new(8.0, 90.0);
+        student.previous_grades = Some(85.0);
+        let evidence = index.nearest(&student, 2);
+        assert_eq!(evidence.k, 2);
+        assert_eq!(evidence.compared_on.len(), 3);
+        assert_eq!(evidence.students[0].distance, 0.0);
+        assert_eq!(evidence.students[1].source, PeerSource::RecordedOutcome);
+        assert_eq!(evidence.pass_rate, 0.5);
+
+        // Without previous grades only hours and attendance are compared
+        let evidence = index.nearest(&StudentFeatures::new(7.0, 88.0), 1);
+        assert_eq!(evidence.compared_on, vec!["study_hours".to_string(), "attendance".to_string()]);
+        assert_eq!(evidence.students[0].previous_grades, 60.0);
+        assert_eq!(index.nearest(&student, 10).k, 5);
+    }
+
+    #[test]
+    fn test_incremental_updates_stay_within_drift_cap() {
+        let (capped, drift, was_capped) = cap_drift(&[0.0, 0.0], &[3.0, 4.0], 1.0);
+        assert!(was_capped);
+        assert_eq!(drift, 1.0);
+        assert!((capped[0] - 0.6).abs() < 1e-12 && (capped[1] - 0.8).abs() < 1e-12);
+        assert!(!cap_drift(&[0.0], &[0.5], 1.0).2);
+
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        // Diligent students who failed anyway pull the weights the other way
+        let surprises = parse_dataset("hours,attendance,previous_grades,sleep_hours,pass\n\
+            9,95,85,8,0\n\
+            8,92,80,7,0\n\
+            10,98,90,8,0\n").unwrap();
+        let options = IncrementalOptions { learning_rate: 0.5, epochs: 50, max_weight_drift: 0.5 };
+
+        let (first, step) = model.with_incremental_update("1.0.0", &surprises, None, &options).unwrap();
+        assert!(step.capped);
+        assert!((step.weight_drift - 0.5).abs() < 1e-9);
+        // The calibrator was fitted to the old weights, so compare raw scores
+        let diligent = model.prepare(&StudentFeatures::new(9.0, 95.0));
+        assert!(first.predict_proba(&StudentFeatures::new(9.0, 95.0)) < model.estimator().predict_proba(&diligent));
+        assert!(model.metadata().calibration.is_some() && first.metadata().calibration.is_none());
+
+        // Later steps stay anchored to the fully trained version
+        let (second, step) = first.with_incremental_update("1.0.1", &surprises, None, &options).unwrap();
+        let state = second.training().incremental.as_ref().unwrap();
+        assert_eq!((state.base_version.as_str(), state.steps, state.records_seen), ("1.0.0", 2, 6));
+        assert_eq!(step.updated_from, "1.0.1");
+        assert!(state.weight_drift <= 0.5 + 1e-9);
+        assert!(TrainedModel::from_json(&second.to_json().unwrap()).is_ok());
+        // Refitting the config on the dataset would not reproduce the SGD steps
+        assert!(second.with_threshold(&surprises, ThresholdObjective::default()).is_err());
+
+        let tree = TrainingConfig { family: ModelFamily::DecisionTree, ..TrainingConfig::default() };
+        let tree_model = train_model_with_config(DEFAULT_DATA_PATH, &tree).unwrap();
+        assert!(tree_model.with_incremental_update("1.0.0", &surprises, None, &options).is_err());
+        assert!(model.with_incremental_update("1.0.0", &[], None, &options).is_err());
+        for bad in [
+            IncrementalOptions { learning_rate: f64::NAN, ..options.clone() },
+            IncrementalOptions { epochs: 0, ..options.clone() },
+            IncrementalOptions { epochs: usize::MAX, ..options.clone() },
+            IncrementalOptions { max_weight_drift: f64::INFINITY, ..options.clone() },
+        ] {
+            assert!(model.with_incremental_update("1.0.0", &surprises, None, &bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_preprocessing_pipeline() {
+        let rows = vec![vec![2.0, 50.0, 60.0, 5.0], vec![4.0, 70.0, 70.0, 6.0], vec![9.0, 95.0, 90.0, 9.0]];
+        let config = PreprocessingConfig { scaling: ScalingMethod::MinMax, imputation: ImputationStrategy::Median, clip: true };
+        let preprocessor = Preprocessor::fit(&rows, &config);
+
+        let prepared = preprocessor.apply(&StudentFeatures::new(20.0, 10.0));
+        assert_eq!((prepared.hours, prepared.attendance), (9.0, 50.0));
+        assert_eq!((prepared.previous_grades, prepared.sleep_hours), (Some(70.0), Some(6.0)));
+        let unclipped = Preprocessor::fit(&rows, &PreprocessingConfig::default()).apply(&StudentFeatures::new(20.0, 10.0));
+        assert_eq!((unclipped.hours, unclipped.previous_grades, unclipped.sleep_hours), (20.0, Some(70.0), Some(7.0)));
+
+        let training = TrainingConfig { preprocessing: config, ..TrainingConfig::default() };
+        let model = train_model_with_config(DEFAULT_DATA_PATH, &training).unwrap();
+        let records = load_dataset(DEFAULT_DATA_PATH).unwrap();
+        let max_hours = records.iter().map(|r| r.hours).fold(f64::MIN, f64::max);
+        let far_out = StudentFeatures::new(max_hours * 10.0, 90.0);
+        assert_eq!(model.predict_proba(&far_out), model.predict_proba(&StudentFeatures::new(max_hours, 90.0)));
+        // Clipping never hides an out-of-range input from review
+        assert!(model.review(&far_out).out_of_distribution);
+
+        let explanation = model.explain(&StudentFeatures::new(5.0, 80.0));
+        assert_eq!(explanation.contributions.iter().filter(|c| c.imputed).count(), 2);
+
+        let restored = TrainedModel::from_json(&model.to_json().unwrap()).unwrap();
+        assert!((restored.predict_proba(&far_out) - model.predict_proba(&far_out)).abs() < 1e-12);
+        assert_eq!(restored.training().config.preprocessing, config);
+    }
+
+    #[test]
+    fn test_risk_forecast_from_weekly_history() {
+        let line = LinearTrend::fit(&[(1.0, 3.0), (2.0, 4.0), (3.0, 5.0)]).unwrap();
+        assert!((line.slope - 1.0).abs() < 1e-12 && line.residual_std.unwrap() < 1e-12);
+        let band = line.project(6.0);
+        assert!((band.value - 8.0).abs() < 1e-12 && band.upper.unwrap() - band.lower.unwrap() < 1e-9);
+        // Two weeks fit any line exactly, so there is no band to report
+        let two_weeks = LinearTrend::fit(&[(1.0, 3.0), (2.0, 5.0)]).unwrap();
+        assert!(two_weeks.residual_std.is_none() && two_weeks.project(4.0).lower.is_none());
+        let noisy = LinearTrend::fit(&[(1.0, 3.0), (2.0, 5.0), (3.0, 4.0), (4.0, 6.0)]).unwrap();
+        // Bands widen further from the observed weeks
+        let (near, far) = (noisy.project(5.0), noisy.project(10.0));
+        assert!(far.upper.unwrap() - far.lower.unwrap() > near.upper.unwrap() - near.lower.unwrap());
+
+        let model = train_model(DEFAULT_DATA_PATH).unwrap();
+        let history = |hours: [f64; 4], attendance: [f64; 4]| -> Vec<WeeklyObservation> {
+            (0..4).map(|i| WeeklyObservation { week: i + 1, study_hours: hours[i], attendance: attendance[i] }).collect()
+        };
+        let improving = forecast_risk(&model, &history([3.0, 4.0, 5.0, 6.0], [60.0, 68.0, 75.0, 82.0]), 4, None, None).unwrap();
+        let declining = forecast_risk(&model, &history([6.0, 5.0, 4.0, 3.0], [82.0, 75.0, 68.0, 60.0]), 4, None, None).unwrap();
+
+        assert_eq!(improving.end_of_term_week, 8);
+        assert_eq!(improving.projections.len(), 4);
+        assert!(improving.projections.iter().all(|p| p.attendance.upper.unwrap() <= 100.0));
+        assert!(improving.end_of_term_pass_probability > declining.end_of_term_pass_probability);
+        assert!(declining.pass_probability_lower.unwrap() <= declining.end_of_term_pass_probability);
+        assert!(declining.end_of_term_pass_probability <= declining.pass_probability_upper.unwrap());
+        assert!(forecast_risk(&model, &history([3.0; 4], [60.0; 4])[..1], 4, None, None).is_none());
+        let short = forecast_risk(&model, &history([3.0; 4], [60.0; 4])[..2], 4, None, None).unwrap();
+        assert!(short.pass_probability_lower.is_none() && short.pass_probability_upper.is_none());
+
+        // Projections stop at the end of the term
+        let long = history([3.0, 4.0, 5.0, 6.0], [60.0, 68.0, 75.0, 82.0]);
+        assert!(validate_forecast(&long, MAX_TERM_WEEKS - 4).is_ok());
+        assert!(validate_forecast(&long, MAX_TERM_WEEKS).is_err());
+        assert!(validate_forecast(&long, usize::MAX).is_err());
+        assert!(forecast_risk(&model, &long, usize::MAX, None, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_class_trends_from_stored_weeks() {
+        let db = Database::new().await.unwrap();
+        let rows = [
+            ("ann", "maths", 1, 4.0, 80.0, true),
+            ("ann", "maths", 2, 6.0, 90.0, true),
+            ("ben", "maths", 1, 2.0, 60.0, false),
+            ("cat", "maths", 1, 9.0, 70.0, true),
+            ("dan", "physics", 1, 1.0, 40.0, false),
+        ];
+        for (name, course, week, study_hours, attendance, predicted_pass) in rows {
+            let record = WeeklyRecord {
+                id: 0,
+                student_name: name.to_string(),
+                course: Some(course.to_string()),
+                cohort: None,
+                week,
+                study_hours,
+                attendance,
+                predicted_pass,
+                confidence: if predicted_pass { 0.8 } else { 0.2 },
+                recorded_at: chrono::Utc::now(),
+            };
+            db.save_weekly_record(&record).await.unwrap();
+        }
+        // Re-recording a week replaces it
+        let again = db.get_weekly_records(&TrendFilter::default()).await.unwrap()[0].clone();
+        db.save_weekly_record(&again).await.unwrap();
+
+        let maths = TrendFilter { course: Some("maths".to_string()), cohort: None };
+        let records = db.get_weekly_records(&maths).await.unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(db.get_weekly_records(&TrendFilter::default()).await.unwrap().len(), 5);
+
+        let trends = TrendsAnalyzer::new().generate_class_trends(&records);
+        assert_eq!(trends.weeks, vec![1, 2]);
+        assert_eq!(trends.student_counts, vec![3, 1]);
+        assert!((trends.avg_study_hours[0] - 5.0).abs() < 1e-12);
+        assert!((trends.median_study_hours[0] - 4.0).abs() < 1e-12);
+        assert!((trends.median_attendance[0] - 70.0).abs() < 1e-12);
+        assert!((trends.pass_rates[0] - 2.0 / 3.0).abs() < 1e-12);
+
+        let weekly = db.get_weekly_trends(&maths).await.unwrap();
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly[0].total_students, 3);
+        assert!((weekly[0].avg_attendance - 70.0).abs() < 1e-9);
+        let ann_maths = ("ann".to_string(), Some("maths".to_string()));
+        assert_eq!(student_histories(&records)[&ann_maths], vec![(4.0, 80.0), (6.0, 90.0)]);
+
+        // The same student's weeks in another course are a separate history
+        let physics: Vec<WeeklyRecord> = [(1, 2.0, 50.0), (2, 3.0, 55.0)]
+            .into_iter()
+            .map(|(week, study_hours, attendance)| WeeklyRecord {
+                student_name: "ann".to_string(),
+                course: Some("physics".to_string()),
+                week,
+                study_hours,
+                attendance,
+                ..again.clone()
+            })
+            .collect();
+        assert_eq!(db.save_weekly_records(&physics).await.unwrap(), 2);
+        let histories = student_histories(&db.get_weekly_records(&TrendFilter::default()).await.unwrap());
+        assert_eq!(histories[&ann_maths], vec![(4.0, 80.0), (6.0, 90.0)]);
+        assert_eq!(histories[&("ann".to_string(), Some("physics".to_string()))], vec![(2.0, 50.0), (3.0, 55.0)]);
+
+        // ...and count as another student in the class-wide weekly trends
+        let everyone = db.get_weekly_records(&TrendFilter::default()).await.unwrap();
+        assert_eq!(TrendsAnalyzer::new().generate_class_trends(&everyone).student_counts, vec![5, 2]);
+        let weekly = db.get_weekly_trends(&TrendFilter::default()).await.unwrap();
+        assert_eq!(weekly.iter().map(|w| w.total_students).collect::<Vec<_>>(), vec![5, 2]);
+    }
+
+    #[test]
+    fn test_weekly_observation_bounds() {
+        let week = WeeklyObservation { week: 1, study_hours: 6.0, attendance: 85.0 };
+        assert!(week.validate().is_ok());
+        assert!(WeeklyObservation { week: MAX_TERM_WEEKS, study_hours: 0.0, attendance: 100.0 }.validate().is_ok());
+        assert!(WeeklyObservation { week: 0, ..week }.validate().is_err());
+        assert!(WeeklyObservation { week: MAX_TERM_WEEKS + 1, ..week }.validate().is_err());
+        assert!(WeeklyObservation { study_hours: -1.0, ..week }.validate().is_err());
+        assert!(WeeklyObservation { study_hours: f64::NAN, ..week }.validate().is_err());
+        assert!(WeeklyObservation { attendance: 101.0, ..week }.validate().is_err());
+        assert!(WeeklyObservation { attendance: f64::NAN, ..week }.validate().is_err());
     }
-}
\ No newline at end of file
+}